@@ -0,0 +1,94 @@
+//! Compares the memory retained by loading a text column with few distinct
+//! values as `String` and as `Interned`
+//!
+//! Run with `cargo bench --bench interning`.
+
+extern crate diesel;
+extern crate diesel_custom_type;
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::hint::black_box;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+use diesel::pg::Pg;
+use diesel::types::{FromSql, Text};
+use diesel_custom_type::adapters::{InternCache, InternPool, Interned};
+
+// Counts live heap memory and allocations, so the retained size of a result
+// set can be measured
+struct CountingAlloc;
+
+static LIVE_BYTES: AtomicUsize = AtomicUsize::new(0);
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        LIVE_BYTES.fetch_add(layout.size(), Ordering::Relaxed);
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        LIVE_BYTES.fetch_sub(layout.size(), Ordering::Relaxed);
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+struct Categories;
+
+impl InternPool for Categories {
+    fn cache() -> &'static InternCache {
+        static CACHE: InternCache = InternCache::new(64);
+        &CACHE
+    }
+}
+
+const ROWS: usize = 2_000_000;
+const DISTINCT: usize = 30;
+
+struct Measurement {
+    time: Duration,
+    retained_bytes: usize,
+    allocations: usize,
+}
+
+fn measure<T, F: Fn(&[u8]) -> T>(raw: &[Vec<u8>], load: F) -> Measurement {
+    let (bytes_before, allocations_before) = (LIVE_BYTES.load(Ordering::Relaxed), ALLOCATIONS.load(Ordering::Relaxed));
+    let start = Instant::now();
+    let rows = black_box(raw).iter().map(|v| load(v)).collect::<Vec<T>>();
+    let time = start.elapsed();
+    let measurement = Measurement {
+        time,
+        retained_bytes: LIVE_BYTES.load(Ordering::Relaxed) - bytes_before,
+        allocations: ALLOCATIONS.load(Ordering::Relaxed) - allocations_before,
+    };
+    drop(black_box(rows));
+    measurement
+}
+
+fn report(name: &str, m: &Measurement) {
+    println!(
+        "{:<10} {:>8.1?} {:>10} KiB retained {:>9} allocations",
+        name,
+        m.time,
+        m.retained_bytes / 1024,
+        m.allocations
+    );
+}
+
+fn main() {
+    let raw = (0..ROWS)
+        .map(|i| format!("category number {}", i % DISTINCT).into_bytes())
+        .collect::<Vec<_>>();
+
+    let strings = measure(&raw, |v| <String as FromSql<Text, Pg>>::from_sql(Some(v)).unwrap());
+    let interned = measure(&raw, |v| <Interned<Categories> as FromSql<Text, Pg>>::from_sql(Some(v)).unwrap());
+
+    println!("{} rows with {} distinct values", ROWS, DISTINCT);
+    report("String", &strings);
+    report("Interned", &interned);
+}
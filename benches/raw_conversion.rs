@@ -0,0 +1,77 @@
+//! Compares checked and unchecked conversions of a large batch of raw values
+//!
+//! Run with `cargo bench --bench raw_conversion`.
+
+extern crate diesel;
+extern crate diesel_custom_type;
+
+use std::error::Error;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use diesel_custom_type::{CustomSqlType, TrustedRawMapping};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[repr(i16)]
+enum Status {
+    Open = 1,
+    Blocked = 2,
+    Closed = 3,
+    Archived = 4,
+}
+
+impl CustomSqlType for Status {
+    type DataBaseType = diesel::types::SmallInt;
+    type RawType = i16;
+    type Error = Box<dyn Error + Send + Sync>;
+
+    fn to_database_type(&self) -> i16 {
+        *self as i16
+    }
+
+    fn from_database_type(v: &i16) -> Result<Self, Self::Error> {
+        match *v {
+            1 => Ok(Status::Open),
+            2 => Ok(Status::Blocked),
+            3 => Ok(Status::Closed),
+            4 => Ok(Status::Archived),
+            v => Err(format!("Unknown status {}", v).into()),
+        }
+    }
+}
+
+// `from_database_type` accepts exactly the discriminants of `Status`
+unsafe impl TrustedRawMapping for Status {
+    unsafe fn from_raw_unchecked(raw: i16) -> Self {
+        unsafe { std::mem::transmute::<i16, Status>(raw) }
+    }
+}
+
+const BATCH: usize = 10_000_000;
+const RUNS: usize = 5;
+
+fn measure<F: FnMut(&i16) -> Status>(raw: &[i16], mut convert: F) -> Duration {
+    (0..RUNS)
+        .map(|_| {
+            let start = Instant::now();
+            for v in black_box(raw) {
+                black_box(convert(v));
+            }
+            start.elapsed()
+        })
+        .min()
+        .expect("RUNS is not 0")
+}
+
+fn main() {
+    let raw = (0..BATCH).map(|i| (i % 4) as i16 + 1).collect::<Vec<_>>();
+
+    let checked = measure(&raw, |v| Status::from_database_type(v).unwrap());
+    // every value in `raw` is between 1 and 4
+    let unchecked = measure(&raw, |v| unsafe { Status::from_raw_unchecked(*v) });
+
+    let per_value = |d: Duration| d.as_secs_f64() * 1e9 / BATCH as f64;
+    println!("{} values, best of {} runs", BATCH, RUNS);
+    println!("from_database_type: {:?} ({:.2} ns/value)", checked, per_value(checked));
+    println!("from_raw_unchecked: {:?} ({:.2} ns/value)", unchecked, per_value(unchecked));
+}
@@ -0,0 +1,270 @@
+use std::collections::{BTreeSet, HashSet};
+use std::error::Error;
+use std::hash::{BuildHasher, Hash};
+use std::io::Write;
+use std::ops::{Deref, DerefMut};
+
+use diesel::expression::AsExpression;
+use diesel::expression::bound::Bound;
+use diesel::pg::Pg;
+use diesel::row::Row;
+use diesel::types::{Array, FromSql, FromSqlRow, HasSqlType, IsNull, ToSql};
+use diesel::Queryable;
+
+use super::InvalidValueError;
+use CustomSqlType;
+
+/// Set types that can be stored by `ArraySet`, implemented for `HashSet` and
+/// `BTreeSet`
+pub trait SetCollection: Default {
+    /// Type of the elements
+    type Item;
+
+    /// Adds `v` to the set, returns false if it was already present
+    fn insert_item(&mut self, v: Self::Item) -> bool;
+}
+
+impl<T: Eq + Hash, S: BuildHasher + Default> SetCollection for HashSet<T, S> {
+    type Item = T;
+
+    fn insert_item(&mut self, v: T) -> bool {
+        self.insert(v)
+    }
+}
+
+impl<T: Ord> SetCollection for BTreeSet<T> {
+    type Item = T;
+
+    fn insert_item(&mut self, v: T) -> bool {
+        self.insert(v)
+    }
+}
+
+/// Set of custom type values stored in an `Array` column
+///
+/// `S` is a `HashSet` or `BTreeSet` of a type implementing `CustomSqlType`,
+/// the column type is `Array` of its `DataBaseType`. Elements are written
+/// sorted by their raw value, so the same set is always written as the same
+/// array.
+///
+/// Reading an array that contains an element more than once fails, set
+/// `COLLAPSE_DUPLICATES` to `true` (or use
+/// [`LenientArraySet`](type.LenientArraySet.html)) to keep a single copy
+/// instead.
+///
+/// Only available with the `array-set` feature.
+///
+/// ```
+/// # #[macro_use] extern crate diesel;
+/// # #[macro_use] extern crate diesel_custom_type;
+/// # use std::error::Error;
+/// use std::collections::{BTreeSet, HashSet};
+/// use diesel::prelude::*;
+/// use diesel::pg::Pg;
+/// use diesel::types::{Array, FromSql, SmallInt, ToSql};
+/// use diesel_custom_type::CustomSqlType;
+/// use diesel_custom_type::adapters::{ArraySet, LenientArraySet};
+///
+/// #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+/// enum Tag {
+///     Urgent = 3,
+///     Billing = 1,
+///     Support = 2,
+/// }
+///
+/// impl CustomSqlType for Tag {
+///     type DataBaseType = SmallInt;
+///     type RawType = i16;
+///     type Error = Box<Error + Send + Sync>;
+///
+///     fn to_database_type(&self) -> i16 {
+///         *self as i16
+///     }
+///
+///     fn from_database_type(v: &i16) -> Result<Self, Self::Error> {
+///         match *v {
+///             1 => Ok(Tag::Billing),
+///             2 => Ok(Tag::Support),
+///             3 => Ok(Tag::Urgent),
+///             v => Err(format!("Unknown tag {}", v).into()),
+///         }
+///     }
+/// }
+///
+/// register_custom_type!(Tag);
+///
+/// table! {
+///     tickets {
+///         id -> Integer,
+///         tags -> Array<SmallInt>,
+///     }
+/// }
+///
+/// # fn main() {
+/// type ST = Array<SmallInt>;
+///
+/// let tags = ArraySet([Tag::Urgent, Tag::Billing].iter().cloned().collect::<HashSet<_>>());
+/// let mut bytes = Vec::new();
+/// ToSql::<ST, Pg>::to_sql(&tags, &mut bytes).unwrap();
+/// let read = <ArraySet<HashSet<Tag>> as FromSql<ST, Pg>>::from_sql(Some(&bytes)).unwrap();
+/// assert_eq!(read, tags);
+///
+/// // written sorted by the raw value, not by the order of `Ord`
+/// let raw = <Vec<i16> as FromSql<ST, Pg>>::from_sql(Some(&bytes)).unwrap();
+/// assert_eq!(raw, [1, 3]);
+///
+/// let tags = ArraySet(vec![Tag::Support, Tag::Urgent].into_iter().collect::<BTreeSet<_>>());
+/// let mut bytes = Vec::new();
+/// ToSql::<ST, Pg>::to_sql(&tags, &mut bytes).unwrap();
+/// let read = <ArraySet<BTreeSet<Tag>> as FromSql<ST, Pg>>::from_sql(Some(&bytes)).unwrap();
+/// assert_eq!(read, tags);
+///
+/// let mut duplicates = Vec::new();
+/// ToSql::<ST, Pg>::to_sql(&vec![2i16, 1, 2], &mut duplicates).unwrap();
+/// let err = <ArraySet<BTreeSet<Tag>> as FromSql<ST, Pg>>::from_sql(Some(&duplicates)).unwrap_err();
+/// assert_eq!(err.to_string(), "Invalid value `array` for ArraySet: element 3 is a duplicate");
+/// let read = <LenientArraySet<HashSet<Tag>> as FromSql<ST, Pg>>::from_sql(Some(&duplicates)).unwrap();
+/// assert_eq!(read.len(), 2);
+///
+/// let _query = tickets::table.filter(tickets::tags.eq(tags));
+/// # }
+/// ```
+///
+/// Round trip through a live database:
+///
+/// ```no_run
+/// # extern crate diesel;
+/// # extern crate diesel_custom_type;
+/// use std::collections::BTreeSet;
+/// use diesel::{select, Connection, LoadDsl};
+/// use diesel::expression::AsExpression;
+/// use diesel::pg::PgConnection;
+/// use diesel::types::{Array, Text};
+/// use diesel_custom_type::adapters::{ArraySet, Email};
+///
+/// # fn main() {
+/// let conn = PgConnection::establish(&::std::env::var("DATABASE_URL").unwrap()).unwrap();
+/// let emails: BTreeSet<Email> = ["b@example.com", "a@example.com"].iter().map(|e| e.parse().unwrap()).collect();
+/// let emails = ArraySet(emails);
+/// let expr = AsExpression::<Array<Text>>::as_expression(&emails);
+/// assert_eq!(select(expr).get_result::<ArraySet<BTreeSet<Email>>>(&conn).unwrap(), emails);
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ArraySet<S, const COLLAPSE_DUPLICATES: bool = false>(pub S);
+
+/// `ArraySet` keeping a single copy of duplicate elements while reading
+pub type LenientArraySet<S> = ArraySet<S, true>;
+
+impl<S, const COLLAPSE_DUPLICATES: bool> ArraySet<S, COLLAPSE_DUPLICATES> {
+    /// Unwraps the contained set
+    pub fn into_inner(self) -> S {
+        self.0
+    }
+}
+
+impl<S, const COLLAPSE_DUPLICATES: bool> Deref for ArraySet<S, COLLAPSE_DUPLICATES> {
+    type Target = S;
+
+    fn deref(&self) -> &S {
+        &self.0
+    }
+}
+
+impl<S, const COLLAPSE_DUPLICATES: bool> DerefMut for ArraySet<S, COLLAPSE_DUPLICATES> {
+    fn deref_mut(&mut self) -> &mut S {
+        &mut self.0
+    }
+}
+
+impl<S, const COLLAPSE_DUPLICATES: bool> From<S> for ArraySet<S, COLLAPSE_DUPLICATES> {
+    fn from(v: S) -> Self {
+        ArraySet(v)
+    }
+}
+
+impl<S, ST, const COLLAPSE_DUPLICATES: bool> ToSql<Array<ST>, Pg> for ArraySet<S, COLLAPSE_DUPLICATES>
+where
+    S: SetCollection,
+    for<'a> &'a S: IntoIterator<Item = &'a S::Item>,
+    S::Item: CustomSqlType<DataBaseType = ST>,
+    <S::Item as CustomSqlType>::RawType: ToSql<ST, Pg> + Ord,
+    Pg: HasSqlType<ST>,
+{
+    fn to_sql<W: Write>(&self, out: &mut W) -> Result<IsNull, Box<dyn Error + Send + Sync>> {
+        let mut raw = self
+            .0
+            .into_iter()
+            .map(CustomSqlType::try_to_database_type)
+            .collect::<Result<Vec<_>, _>>()?;
+        raw.sort();
+        ToSql::<Array<ST>, Pg>::to_sql(&raw, out)
+    }
+}
+
+impl<S, ST, const COLLAPSE_DUPLICATES: bool> FromSql<Array<ST>, Pg> for ArraySet<S, COLLAPSE_DUPLICATES>
+where
+    S: SetCollection,
+    S::Item: FromSql<ST, Pg>,
+    Pg: HasSqlType<ST>,
+{
+    fn from_sql(bytes: Option<&[u8]>) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        let mut set = S::default();
+        for (i, v) in <Vec<S::Item> as FromSql<Array<ST>, Pg>>::from_sql(bytes)?.into_iter().enumerate() {
+            if !set.insert_item(v) && !COLLAPSE_DUPLICATES {
+                // PostgreSQL arrays start at 1
+                let reason = format!("element {} is a duplicate", i + 1);
+                return Err(Box::new(InvalidValueError::new("ArraySet", "array", reason)));
+            }
+        }
+        Ok(ArraySet(set))
+    }
+}
+
+impl<S, ST, const COLLAPSE_DUPLICATES: bool> FromSqlRow<Array<ST>, Pg> for ArraySet<S, COLLAPSE_DUPLICATES>
+where
+    S: SetCollection,
+    S::Item: FromSql<ST, Pg>,
+    Pg: HasSqlType<ST>,
+{
+    fn build_from_row<R: Row<Pg>>(row: &mut R) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        <Self as FromSql<Array<ST>, Pg>>::from_sql(row.take())
+    }
+}
+
+impl<S, ST, const COLLAPSE_DUPLICATES: bool> Queryable<Array<ST>, Pg> for ArraySet<S, COLLAPSE_DUPLICATES>
+where
+    S: SetCollection,
+    S::Item: FromSql<ST, Pg>,
+    Pg: HasSqlType<ST>,
+{
+    type Row = Self;
+
+    fn build(row: Self) -> Self {
+        row
+    }
+}
+
+impl<S, ST, const COLLAPSE_DUPLICATES: bool> AsExpression<Array<ST>> for ArraySet<S, COLLAPSE_DUPLICATES>
+where
+    S: SetCollection,
+    S::Item: CustomSqlType<DataBaseType = ST>,
+{
+    type Expression = Bound<Array<ST>, ArraySet<S, COLLAPSE_DUPLICATES>>;
+
+    fn as_expression(self) -> Self::Expression {
+        Bound::new(self)
+    }
+}
+
+impl<'a, S, ST, const COLLAPSE_DUPLICATES: bool> AsExpression<Array<ST>> for &'a ArraySet<S, COLLAPSE_DUPLICATES>
+where
+    S: SetCollection,
+    S::Item: CustomSqlType<DataBaseType = ST>,
+{
+    type Expression = Bound<Array<ST>, &'a ArraySet<S, COLLAPSE_DUPLICATES>>;
+
+    fn as_expression(self) -> Self::Expression {
+        Bound::new(self)
+    }
+}
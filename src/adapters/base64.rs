@@ -0,0 +1,54 @@
+// Standard base64 encoding, shared by `Base64Bytes` and `Encrypted`
+
+const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Encodes `bytes` using the standard alphabet
+pub(crate) fn encode(bytes: &[u8], pad: bool) -> String {
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = chunk.iter().enumerate().fold(0u32, |n, (i, b)| n | (u32::from(*b) << (16 - 8 * i)));
+        for i in 0..chunk.len() + 1 {
+            out.push(ALPHABET[(n >> (18 - 6 * i)) as usize & 0x3f] as char);
+        }
+        if pad {
+            for _ in chunk.len()..3 {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+fn value(c: u8) -> Option<u32> {
+    ALPHABET.iter().position(|a| *a == c).map(|v| v as u32)
+}
+
+/// Decodes `s` using the standard alphabet, padding is optional
+///
+/// On failure the byte offset of the problem is returned along with a
+/// description
+pub(crate) fn decode(s: &str) -> Result<Vec<u8>, (usize, &'static str)> {
+    let bytes = s.as_bytes();
+    let data = bytes.iter().rposition(|b| *b != b'=').map_or(&bytes[..0], |i| &bytes[..i + 1]);
+    let padding = bytes.len() - data.len();
+    if padding > 2 || padding > 0 && !bytes.len().is_multiple_of(4) {
+        return Err((data.len(), "invalid padding"));
+    }
+    if data.len() % 4 == 1 {
+        return Err((data.len() - 1, "invalid length"));
+    }
+    let mut out = Vec::with_capacity(data.len() / 4 * 3 + 2);
+    for (i, chunk) in data.chunks(4).enumerate() {
+        let mut n = 0;
+        for (j, c) in chunk.iter().enumerate() {
+            match value(*c) {
+                Some(v) => n |= v << (18 - 6 * j),
+                None => return Err((i * 4 + j, "invalid character")),
+            }
+        }
+        for j in 0..chunk.len() - 1 {
+            out.push((n >> (16 - 8 * j)) as u8);
+        }
+    }
+    Ok(out)
+}
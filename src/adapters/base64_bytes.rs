@@ -0,0 +1,110 @@
+use std::ops::Deref;
+
+use diesel::types::Text;
+
+use CustomSqlType;
+use super::InvalidValueError;
+use super::base64::{decode, encode};
+
+// Longest part of an invalid value included in errors
+const MAX_ERROR_VALUE_LEN: usize = 64;
+
+fn excerpt(v: &str) -> String {
+    if v.len() <= MAX_ERROR_VALUE_LEN {
+        return v.to_owned();
+    }
+    let mut end = MAX_ERROR_VALUE_LEN;
+    while !v.is_char_boundary(end) {
+        end -= 1;
+    }
+    format!("{}... ({} bytes)", &v[..end], v.len())
+}
+
+/// Binary payload stored base64 encoded in a `Text` column
+///
+/// Uses the standard alphabet. Values are written with padding unless `PAD`
+/// is `false`. Reading accepts both forms. Reading a value that would decode
+/// to more than `MAX_LEN` bytes results in an error, which guards against
+/// pathological rows. Errors only contain the length or the start of an
+/// invalid value, never the whole value.
+///
+/// Only available with the `base64` feature.
+///
+/// ```
+/// # extern crate diesel;
+/// # extern crate diesel_custom_type;
+/// use diesel_custom_type::CustomSqlType;
+/// use diesel_custom_type::adapters::Base64Bytes;
+///
+/// # fn main() {
+/// let payload: Base64Bytes = Base64Bytes(b"hello world".to_vec());
+/// assert_eq!(payload.to_database_type(), "aGVsbG8gd29ybGQ=");
+/// assert_eq!(<Base64Bytes>::from_database_type(&payload.to_database_type()).unwrap(), payload);
+/// assert_eq!(Base64Bytes::<false>(b"hello world".to_vec()).to_database_type(), "aGVsbG8gd29ybGQ");
+/// assert_eq!(<Base64Bytes>::from_database_type(&"aGVsbG8gd29ybGQ".to_string()).unwrap(), payload);
+///
+/// let empty: Base64Bytes = Base64Bytes(Vec::new());
+/// assert_eq!(empty.to_database_type(), "");
+/// assert_eq!(<Base64Bytes>::from_database_type(&String::new()).unwrap(), empty);
+///
+/// let err = <Base64Bytes>::from_database_type(&"aGVs*G8=".to_string()).unwrap_err();
+/// assert_eq!(err.to_string(), "Invalid value `aGVs*G8=` for Base64Bytes: invalid character at byte 4");
+///
+/// let err = Base64Bytes::<true, 4>::from_database_type(&"aGVsbG8=".to_string()).unwrap_err();
+/// assert_eq!(err.to_string(), "Invalid value `5 decoded bytes` for Base64Bytes: payload is larger than 4 bytes");
+///
+/// let long = format!("{}*", "A".repeat(99));
+/// let err = <Base64Bytes>::from_database_type(&long).unwrap_err();
+/// assert_eq!(err.value, format!("{}... (100 bytes)", "A".repeat(64)));
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct Base64Bytes<const PAD: bool = true, const MAX_LEN: usize = { usize::MAX }>(pub Vec<u8>);
+
+impl<const PAD: bool, const MAX_LEN: usize> Base64Bytes<PAD, MAX_LEN> {
+    /// Unwraps the contained bytes
+    pub fn into_inner(self) -> Vec<u8> {
+        self.0
+    }
+}
+
+impl<const PAD: bool, const MAX_LEN: usize> Deref for Base64Bytes<PAD, MAX_LEN> {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl<const PAD: bool, const MAX_LEN: usize> From<Vec<u8>> for Base64Bytes<PAD, MAX_LEN> {
+    fn from(v: Vec<u8>) -> Self {
+        Base64Bytes(v)
+    }
+}
+
+impl<const PAD: bool, const MAX_LEN: usize> CustomSqlType for Base64Bytes<PAD, MAX_LEN> {
+    type DataBaseType = Text;
+    type RawType = String;
+    type Error = InvalidValueError;
+
+    fn to_database_type(&self) -> String {
+        encode(&self.0, PAD)
+    }
+
+    fn from_database_type(v: &String) -> Result<Self, InvalidValueError> {
+        let decoded_len = v.trim_end_matches('=').len() * 3 / 4;
+        if decoded_len > MAX_LEN {
+            let reason = format!("payload is larger than {} bytes", MAX_LEN);
+            return Err(InvalidValueError::new("Base64Bytes", format!("{} decoded bytes", decoded_len), reason));
+        }
+        match decode(v) {
+            Ok(bytes) => Ok(Base64Bytes(bytes)),
+            Err((offset, reason)) => {
+                let reason = format!("{} at byte {}", reason, offset);
+                Err(InvalidValueError::new("Base64Bytes", excerpt(v), reason))
+            }
+        }
+    }
+}
+
+register_custom_type!(impl[const PAD: bool, const MAX_LEN: usize] Base64Bytes<PAD, MAX_LEN> where);
@@ -0,0 +1,171 @@
+use std::convert::Infallible;
+use std::fmt;
+use std::str::FromStr;
+
+use diesel::types::Text;
+
+use {CustomSqlType, Variants};
+use super::InvalidValueError;
+
+macro_rules! country_codes {
+    ($($Code:ident,)+) => {
+        /// ISO 3166-1 alpha-2 country code stored in a `Text` column
+        ///
+        /// Written as the uppercase two letter code. Reading an unknown code
+        /// results in an error, use
+        /// [`LenientCountryCode`](enum.LenientCountryCode.html) to keep
+        /// unknown codes instead.
+        ///
+        /// ```
+        /// # extern crate diesel;
+        /// # extern crate diesel_custom_type;
+        /// use diesel_custom_type::{CustomSqlType, Variants};
+        /// use diesel_custom_type::adapters::{CountryCode, LenientCountryCode};
+        ///
+        /// # fn main() {
+        /// assert_eq!(CountryCode::variants().len(), 249);
+        /// for code in CountryCode::variants() {
+        ///     let raw = code.to_database_type();
+        ///     assert_eq!(raw, code.as_str());
+        ///     assert_eq!(CountryCode::from_database_type(&raw).unwrap(), *code);
+        ///     assert_eq!(raw.parse::<CountryCode>().unwrap(), *code);
+        /// }
+        ///
+        /// let err = CountryCode::from_database_type(&"XK".to_string()).unwrap_err();
+        /// assert_eq!(err.to_string(), "Invalid value `XK` for CountryCode: unknown country code");
+        ///
+        /// let lenient = LenientCountryCode::from_database_type(&"XK".to_string()).unwrap();
+        /// assert_eq!(lenient, LenientCountryCode::Unassigned("XK".to_string()));
+        /// assert_eq!(lenient.to_database_type(), "XK");
+        /// let known = LenientCountryCode::from_database_type(&"DE".to_string()).unwrap();
+        /// assert_eq!(known, LenientCountryCode::Assigned(CountryCode::DE));
+        /// # }
+        /// ```
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+        pub enum CountryCode {
+            $($Code,)+
+        }
+
+        static COUNTRY_CODES: &[CountryCode] = &[$(CountryCode::$Code,)+];
+
+        impl CountryCode {
+            /// Returns the alpha-2 code
+            pub fn as_str(&self) -> &'static str {
+                match *self {
+                    $(CountryCode::$Code => stringify!($Code),)+
+                }
+            }
+
+            fn from_code(code: &str) -> Option<Self> {
+                match code {
+                    $(stringify!($Code) => Some(CountryCode::$Code),)+
+                    _ => None,
+                }
+            }
+        }
+    }
+}
+
+country_codes! {
+    AD, AE, AF, AG, AI, AL, AM, AO, AQ, AR, AS, AT, AU, AW, AX, AZ,
+    BA, BB, BD, BE, BF, BG, BH, BI, BJ, BL, BM, BN, BO, BQ, BR, BS, BT, BV, BW, BY, BZ,
+    CA, CC, CD, CF, CG, CH, CI, CK, CL, CM, CN, CO, CR, CU, CV, CW, CX, CY, CZ,
+    DE, DJ, DK, DM, DO, DZ,
+    EC, EE, EG, EH, ER, ES, ET,
+    FI, FJ, FK, FM, FO, FR,
+    GA, GB, GD, GE, GF, GG, GH, GI, GL, GM, GN, GP, GQ, GR, GS, GT, GU, GW, GY,
+    HK, HM, HN, HR, HT, HU,
+    ID, IE, IL, IM, IN, IO, IQ, IR, IS, IT,
+    JE, JM, JO, JP,
+    KE, KG, KH, KI, KM, KN, KP, KR, KW, KY, KZ,
+    LA, LB, LC, LI, LK, LR, LS, LT, LU, LV, LY,
+    MA, MC, MD, ME, MF, MG, MH, MK, ML, MM, MN, MO, MP, MQ, MR, MS, MT, MU, MV, MW, MX, MY, MZ,
+    NA, NC, NE, NF, NG, NI, NL, NO, NP, NR, NU, NZ,
+    OM,
+    PA, PE, PF, PG, PH, PK, PL, PM, PN, PR, PS, PT, PW, PY,
+    QA,
+    RE, RO, RS, RU, RW,
+    SA, SB, SC, SD, SE, SG, SH, SI, SJ, SK, SL, SM, SN, SO, SR, SS, ST, SV, SX, SY, SZ,
+    TC, TD, TF, TG, TH, TJ, TK, TL, TM, TN, TO, TR, TT, TV, TW, TZ,
+    UA, UG, UM, US, UY, UZ,
+    VA, VC, VE, VG, VI, VN, VU,
+    WF, WS,
+    YE, YT,
+    ZA, ZM, ZW,
+}
+
+impl Variants for CountryCode {
+    fn variants() -> &'static [Self] {
+        COUNTRY_CODES
+    }
+}
+
+impl fmt::Display for CountryCode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for CountryCode {
+    type Err = InvalidValueError;
+
+    fn from_str(s: &str) -> Result<Self, InvalidValueError> {
+        CountryCode::from_code(s).ok_or_else(|| InvalidValueError::new("CountryCode", s, "unknown country code"))
+    }
+}
+
+impl CustomSqlType for CountryCode {
+    type DataBaseType = Text;
+    type RawType = String;
+    type Error = InvalidValueError;
+
+    fn to_database_type(&self) -> String {
+        self.as_str().to_owned()
+    }
+
+    fn from_database_type(v: &String) -> Result<Self, InvalidValueError> {
+        v.parse()
+    }
+}
+
+register_custom_type!(CountryCode);
+
+/// Country code column that may contain codes unknown to
+/// [`CountryCode`](enum.CountryCode.html)
+///
+/// Unknown codes are kept as `Unassigned` and written back unchanged.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum LenientCountryCode {
+    /// A code from the ISO 3166-1 list
+    Assigned(CountryCode),
+    /// Any other code found in the database
+    Unassigned(String),
+}
+
+impl From<CountryCode> for LenientCountryCode {
+    fn from(v: CountryCode) -> Self {
+        LenientCountryCode::Assigned(v)
+    }
+}
+
+impl CustomSqlType for LenientCountryCode {
+    type DataBaseType = Text;
+    type RawType = String;
+    type Error = Infallible;
+
+    fn to_database_type(&self) -> String {
+        match *self {
+            LenientCountryCode::Assigned(ref code) => code.to_database_type(),
+            LenientCountryCode::Unassigned(ref code) => code.clone(),
+        }
+    }
+
+    fn from_database_type(v: &String) -> Result<Self, Infallible> {
+        Ok(match CountryCode::from_code(v) {
+            Some(code) => LenientCountryCode::Assigned(code),
+            None => LenientCountryCode::Unassigned(v.clone()),
+        })
+    }
+}
+
+register_custom_type!(LenientCountryCode);
@@ -0,0 +1,139 @@
+use std::fmt;
+use std::str::FromStr;
+
+use diesel::types::Text;
+
+use {CustomSqlType, Variants};
+use super::InvalidValueError;
+
+macro_rules! currency_codes {
+    ($($Code:ident => $exponent:expr,)+) => {
+        /// Active ISO 4217 currency code stored in a `Text` column
+        ///
+        /// Always written as the uppercase three letter code. Parsing accepts
+        /// any case, but reading only accepts the uppercase code, so a stored
+        /// value is never changed silently by writing it back. Unknown codes
+        /// result in an error.
+        ///
+        /// ```
+        /// # extern crate diesel;
+        /// # extern crate diesel_custom_type;
+        /// use diesel_custom_type::CustomSqlType;
+        /// use diesel_custom_type::adapters::CurrencyCode;
+        ///
+        /// # fn main() {
+        /// let eur = "eur".parse::<CurrencyCode>().unwrap();
+        /// assert_eq!(eur, CurrencyCode::EUR);
+        /// assert_eq!(eur.to_database_type(), "EUR");
+        /// assert_eq!(CurrencyCode::from_database_type(&"USD".to_string()).unwrap(), CurrencyCode::USD);
+        ///
+        /// assert_eq!(CurrencyCode::EUR.minor_units(), 2);
+        /// assert_eq!(CurrencyCode::JPY.minor_units(), 0);
+        /// assert_eq!(CurrencyCode::KWD.minor_units(), 3);
+        ///
+        /// let err = CurrencyCode::from_database_type(&"ABC".to_string()).unwrap_err();
+        /// assert_eq!(err.to_string(), "Invalid value `ABC` for CurrencyCode: unknown currency code");
+        /// let err = CurrencyCode::from_database_type(&"usd".to_string()).unwrap_err();
+        /// assert_eq!(err.to_string(), "Invalid value `usd` for CurrencyCode: currency code is not uppercase");
+        /// # }
+        /// ```
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+        pub enum CurrencyCode {
+            $($Code,)+
+        }
+
+        static CURRENCY_CODES: &[CurrencyCode] = &[$(CurrencyCode::$Code,)+];
+
+        impl CurrencyCode {
+            /// Returns the uppercase alphabetic code
+            pub fn as_str(&self) -> &'static str {
+                match *self {
+                    $(CurrencyCode::$Code => stringify!($Code),)+
+                }
+            }
+
+            /// Number of digits after the decimal separator of the minor unit
+            /// (2 for cents, 0 for currencies without minor unit)
+            pub fn minor_units(&self) -> u8 {
+                match *self {
+                    $(CurrencyCode::$Code => $exponent,)+
+                }
+            }
+
+            fn from_code(code: &str) -> Option<Self> {
+                match code {
+                    $(stringify!($Code) => Some(CurrencyCode::$Code),)+
+                    _ => None,
+                }
+            }
+        }
+    }
+}
+
+currency_codes! {
+    AED => 2, AFN => 2, ALL => 2, AMD => 2, ANG => 2, AOA => 2, ARS => 2, AUD => 2,
+    AWG => 2, AZN => 2, BAM => 2, BBD => 2, BDT => 2, BGN => 2, BHD => 3, BIF => 0,
+    BMD => 2, BND => 2, BOB => 2, BOV => 2, BRL => 2, BSD => 2, BTN => 2, BWP => 2,
+    BYN => 2, BZD => 2, CAD => 2, CDF => 2, CHE => 2, CHF => 2, CHW => 2, CLF => 4,
+    CLP => 0, CNY => 2, COP => 2, COU => 2, CRC => 2, CUP => 2, CVE => 2, CZK => 2,
+    DJF => 0, DKK => 2, DOP => 2, DZD => 2, EGP => 2, ERN => 2, ETB => 2, EUR => 2,
+    FJD => 2, FKP => 2, GBP => 2, GEL => 2, GHS => 2, GIP => 2, GMD => 2, GNF => 0,
+    GTQ => 2, GYD => 2, HKD => 2, HNL => 2, HTG => 2, HUF => 2, IDR => 2, ILS => 2,
+    INR => 2, IQD => 3, IRR => 2, ISK => 0, JMD => 2, JOD => 3, JPY => 0, KES => 2,
+    KGS => 2, KHR => 2, KMF => 0, KPW => 2, KRW => 0, KWD => 3, KYD => 2, KZT => 2,
+    LAK => 2, LBP => 2, LKR => 2, LRD => 2, LSL => 2, LYD => 3, MAD => 2, MDL => 2,
+    MGA => 2, MKD => 2, MMK => 2, MNT => 2, MOP => 2, MRU => 2, MUR => 2, MVR => 2,
+    MWK => 2, MXN => 2, MXV => 2, MYR => 2, MZN => 2, NAD => 2, NGN => 2, NIO => 2,
+    NOK => 2, NPR => 2, NZD => 2, OMR => 3, PAB => 2, PEN => 2, PGK => 2, PHP => 2,
+    PKR => 2, PLN => 2, PYG => 0, QAR => 2, RON => 2, RSD => 2, RUB => 2, RWF => 0,
+    SAR => 2, SBD => 2, SCR => 2, SDG => 2, SEK => 2, SGD => 2, SHP => 2, SLE => 2,
+    SOS => 2, SRD => 2, SSP => 2, STN => 2, SVC => 2, SYP => 2, SZL => 2, THB => 2,
+    TJS => 2, TMT => 2, TND => 3, TOP => 2, TRY => 2, TTD => 2, TWD => 2, TZS => 2,
+    UAH => 2, UGX => 0, USD => 2, USN => 2, UYI => 0, UYU => 2, UYW => 4, UZS => 2,
+    VED => 2, VES => 2, VND => 0, VUV => 0, WST => 2, XAF => 0, XCD => 2, XOF => 0,
+    XPF => 0, YER => 2, ZAR => 2, ZMW => 2, ZWG => 2,
+}
+
+impl Variants for CurrencyCode {
+    fn variants() -> &'static [Self] {
+        CURRENCY_CODES
+    }
+}
+
+impl fmt::Display for CurrencyCode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for CurrencyCode {
+    type Err = InvalidValueError;
+
+    fn from_str(s: &str) -> Result<Self, InvalidValueError> {
+        CurrencyCode::from_code(&s.to_ascii_uppercase())
+            .ok_or_else(|| InvalidValueError::new("CurrencyCode", s, "unknown currency code"))
+    }
+}
+
+impl CustomSqlType for CurrencyCode {
+    type DataBaseType = Text;
+    type RawType = String;
+    type Error = InvalidValueError;
+
+    fn to_database_type(&self) -> String {
+        self.as_str().to_owned()
+    }
+
+    fn from_database_type(v: &String) -> Result<Self, InvalidValueError> {
+        match CurrencyCode::from_code(v) {
+            Some(code) => Ok(code),
+            None => {
+                // only report the case if the code itself is known
+                v.parse::<CurrencyCode>()?;
+                Err(InvalidValueError::new("CurrencyCode", v, "currency code is not uppercase"))
+            }
+        }
+    }
+}
+
+register_custom_type!(CurrencyCode);
@@ -0,0 +1,230 @@
+use std::fmt;
+use std::ops::Deref;
+use std::str::FromStr;
+
+use diesel::types::Text;
+
+use CustomSqlType;
+use super::InvalidValueError;
+
+/// Parsed media type like `text/html; charset=utf-8`
+///
+/// Type, subtype and parameter names are case insensitive and stored
+/// lowercase. Parameter values are kept as they are, quoted values may
+/// contain `;` and escaped quotes.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Mime {
+    type_: String,
+    subtype: String,
+    params: Vec<(String, String)>,
+}
+
+/// Media type like `text/html; charset=utf-8` stored in a `Text` column
+///
+/// Reading validates the `type/subtype` structure and parameters, writing
+/// uses the `Display` implementation. Derefs to the parsed
+/// [`Mime`](struct.Mime.html).
+///
+/// Only available with the `mime` feature.
+///
+/// ```
+/// # extern crate diesel;
+/// # extern crate diesel_custom_type;
+/// use diesel_custom_type::CustomSqlType;
+/// use diesel_custom_type::adapters::DbMime;
+///
+/// # fn main() {
+/// let mime = DbMime::from_database_type(&"Text/HTML; charset=utf-8; q=\"a b\"".to_string()).unwrap();
+/// assert_eq!(mime.type_(), "text");
+/// assert_eq!(mime.subtype(), "html");
+/// assert_eq!(mime.get_param("CHARSET"), Some("utf-8"));
+/// assert_eq!(mime.to_database_type(), "text/html; charset=utf-8; q=\"a b\"");
+/// assert_eq!(DbMime::from_database_type(&mime.to_database_type()).unwrap(), mime);
+///
+/// match (mime.type_(), mime.subtype()) {
+///     ("text", "html") => {}
+///     _ => unreachable!(),
+/// }
+///
+/// let quoted = DbMime::from_database_type(&"text/plain; name=\"a;b \\\"c\\\"\"; charset=utf-8".to_string()).unwrap();
+/// assert_eq!(quoted.get_param("name"), Some("a;b \"c\""));
+/// assert_eq!(quoted.get_param("charset"), Some("utf-8"));
+/// assert_eq!(DbMime::from_database_type(&quoted.to_database_type()).unwrap(), quoted);
+///
+/// let err = DbMime::from_database_type(&"image".to_string()).unwrap_err();
+/// assert_eq!(err.to_string(), "Invalid value `image` for DbMime: expected `type/subtype`");
+/// assert!("".parse::<DbMime>().is_err());
+/// assert!("text/plain; charset".parse::<DbMime>().is_err());
+/// assert!("text/plain; name=\"a;b".parse::<DbMime>().is_err());
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DbMime(pub Mime);
+
+fn is_token(s: &str) -> bool {
+    !s.is_empty() && s.bytes().all(|b| b.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~".contains(&b))
+}
+
+fn parse_value(v: &str) -> Option<String> {
+    if v.len() >= 2 && v.starts_with('"') && v.ends_with('"') {
+        let mut value = String::new();
+        let mut chars = v[1..v.len() - 1].chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => value.push(chars.next()?),
+                '"' => return None,
+                c => value.push(c),
+            }
+        }
+        Some(value)
+    } else if is_token(v) {
+        Some(v.to_owned())
+    } else {
+        None
+    }
+}
+
+// Splits at `;` outside of quoted strings, an unterminated string ends up
+// in the last part and fails to parse as a value
+fn split_params(s: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let (mut start, mut in_string, mut escaped) = (0, false, false);
+    for (i, c) in s.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if in_string => escaped = true,
+            '"' => in_string = !in_string,
+            ';' if !in_string => {
+                parts.push(&s[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(&s[start..]);
+    parts
+}
+
+fn parse(s: &str) -> Result<Mime, &'static str> {
+    let mut parts = split_params(s).into_iter();
+    let essence = parts.next().unwrap_or("").trim();
+    let (type_, subtype) = match essence.find('/') {
+        Some(i) => (&essence[..i], &essence[i + 1..]),
+        None => return Err("expected `type/subtype`"),
+    };
+    if !is_token(type_) || !is_token(subtype) {
+        return Err("expected `type/subtype`");
+    }
+    let mut params = Vec::new();
+    for param in parts {
+        let param = param.trim();
+        let (name, value) = match param.find('=') {
+            Some(i) => (&param[..i], &param[i + 1..]),
+            None => return Err("expected `name=value` parameter"),
+        };
+        match parse_value(value) {
+            Some(value) if is_token(name) => params.push((name.to_ascii_lowercase(), value)),
+            _ => return Err("expected `name=value` parameter"),
+        }
+    }
+    Ok(Mime {
+        type_: type_.to_ascii_lowercase(),
+        subtype: subtype.to_ascii_lowercase(),
+        params,
+    })
+}
+
+impl Mime {
+    /// Returns the top level type, e.g. `text`
+    pub fn type_(&self) -> &str {
+        &self.type_
+    }
+
+    /// Returns the subtype, e.g. `html`
+    pub fn subtype(&self) -> &str {
+        &self.subtype
+    }
+
+    /// Returns all parameters in the order they were given
+    pub fn params(&self) -> &[(String, String)] {
+        &self.params
+    }
+
+    /// Returns the value of the parameter `name`
+    pub fn get_param(&self, name: &str) -> Option<&str> {
+        self.params.iter().find(|p| p.0.eq_ignore_ascii_case(name)).map(|p| &*p.1)
+    }
+}
+
+impl fmt::Display for Mime {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}/{}", self.type_, self.subtype)?;
+        for (name, value) in &self.params {
+            if is_token(value) {
+                write!(f, "; {}={}", name, value)?;
+            } else {
+                write!(f, "; {}=\"{}\"", name, value.replace('\\', "\\\\").replace('"', "\\\""))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Mime {
+    type Err = InvalidValueError;
+
+    fn from_str(s: &str) -> Result<Self, InvalidValueError> {
+        parse(s).map_err(|reason| InvalidValueError::new("Mime", s, reason))
+    }
+}
+
+impl DbMime {
+    /// Unwraps the parsed media type
+    pub fn into_inner(self) -> Mime {
+        self.0
+    }
+}
+
+impl Deref for DbMime {
+    type Target = Mime;
+
+    fn deref(&self) -> &Mime {
+        &self.0
+    }
+}
+
+impl From<Mime> for DbMime {
+    fn from(v: Mime) -> Self {
+        DbMime(v)
+    }
+}
+
+impl fmt::Display for DbMime {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl FromStr for DbMime {
+    type Err = InvalidValueError;
+
+    fn from_str(s: &str) -> Result<Self, InvalidValueError> {
+        parse(s).map(DbMime).map_err(|reason| InvalidValueError::new("DbMime", s, reason))
+    }
+}
+
+impl CustomSqlType for DbMime {
+    type DataBaseType = Text;
+    type RawType = String;
+    type Error = InvalidValueError;
+
+    fn to_database_type(&self) -> String {
+        self.to_string()
+    }
+
+    fn from_database_type(v: &String) -> Result<Self, InvalidValueError> {
+        v.parse()
+    }
+}
+
+register_custom_type!(DbMime);
@@ -0,0 +1,117 @@
+use std::convert::Infallible;
+use std::error::Error;
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+
+use diesel::types::Text;
+
+use CustomSqlType;
+use super::InvalidValueError;
+
+/// Filesystem path stored in a `Text` column
+///
+/// Writing a path that is not valid UTF-8 fails instead of storing a lossy
+/// conversion. As diesel 0.8 panics on such errors in a query, build paths
+/// from untrusted input with `try_from_path`, which rejects them up front.
+/// `to_database_type` and `into_raw` can't fail, they replace invalid UTF-8
+/// sequences.
+///
+/// ```
+/// # extern crate diesel;
+/// # extern crate diesel_custom_type;
+/// use std::path::Path;
+/// use diesel::pg::Pg;
+/// use diesel::types::{FromSql, Text, ToSql};
+/// use diesel_custom_type::CustomSqlType;
+/// use diesel_custom_type::adapters::DbPath;
+///
+/// # fn main() {
+/// let path = DbPath::from(Path::new("/srv/uploads/résumé ✓.pdf").to_path_buf());
+/// let mut bytes = Vec::new();
+/// ToSql::<Text, Pg>::to_sql(&path, &mut bytes).unwrap();
+/// let read = <DbPath as FromSql<Text, Pg>>::from_sql(Some(&bytes)).unwrap();
+/// assert_eq!(read, path);
+/// assert_eq!(read.file_name().unwrap(), "résumé ✓.pdf");
+///
+/// # #[cfg(unix)] {
+/// use std::ffi::OsStr;
+/// use std::os::unix::ffi::OsStrExt;
+///
+/// let invalid = Path::new(OsStr::from_bytes(b"/srv/\xff.pdf")).to_path_buf();
+/// let err = DbPath::try_from_path(invalid.clone()).unwrap_err();
+/// assert_eq!(err.to_string(), "Invalid value `/srv/\u{FFFD}.pdf` for DbPath: path is not valid UTF-8");
+///
+/// let invalid = DbPath::from(invalid);
+/// let err = ToSql::<Text, Pg>::to_sql(&invalid, &mut Vec::new()).unwrap_err();
+/// assert_eq!(err.to_string(), "Invalid value `/srv/\u{FFFD}.pdf` for DbPath: path is not valid UTF-8");
+/// assert_eq!(invalid.into_raw(), "/srv/\u{FFFD}.pdf");
+/// # }
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct DbPath(pub PathBuf);
+
+impl DbPath {
+    /// Wraps `path`, failing if it is not valid UTF-8
+    pub fn try_from_path(path: PathBuf) -> Result<Self, InvalidValueError> {
+        check_utf8(&path)?;
+        Ok(DbPath(path))
+    }
+
+    /// Unwraps the contained path
+    pub fn into_inner(self) -> PathBuf {
+        self.0
+    }
+}
+
+impl Deref for DbPath {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl AsRef<Path> for DbPath {
+    fn as_ref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl From<PathBuf> for DbPath {
+    fn from(v: PathBuf) -> Self {
+        DbPath(v)
+    }
+}
+
+impl From<DbPath> for PathBuf {
+    fn from(v: DbPath) -> Self {
+        v.0
+    }
+}
+
+fn check_utf8(path: &Path) -> Result<&str, InvalidValueError> {
+    path.to_str()
+        .ok_or_else(|| InvalidValueError::new("DbPath", path.display(), "path is not valid UTF-8"))
+}
+
+impl CustomSqlType for DbPath {
+    type DataBaseType = Text;
+    type RawType = String;
+    type Error = Infallible;
+
+    /// Lossy conversion, invalid UTF-8 sequences are replaced
+    fn to_database_type(&self) -> String {
+        self.0.to_string_lossy().into_owned()
+    }
+
+    fn try_to_database_type(&self) -> Result<String, Box<dyn Error + Send + Sync>> {
+        Ok(check_utf8(&self.0)?.to_owned())
+    }
+
+    fn from_database_type(v: &String) -> Result<Self, Infallible> {
+        Ok(DbPath(PathBuf::from(v)))
+    }
+}
+
+register_custom_type!(DbPath);
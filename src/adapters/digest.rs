@@ -0,0 +1,147 @@
+use std::convert::TryFrom;
+use std::fmt;
+use std::ops::Deref;
+use std::str::FromStr;
+
+use diesel::types::{Binary, Text};
+
+use CustomSqlType;
+use super::InvalidValueError;
+
+/// 32 byte digest (e.g. SHA-256) stored in a `Binary` column
+///
+/// Reading a value with a different length results in an error. Use
+/// [`HexDigest32`](struct.HexDigest32.html) for digests stored as hex text.
+/// `Display` and `FromStr` use lowercase hex.
+///
+/// ```
+/// # extern crate diesel;
+/// # extern crate diesel_custom_type;
+/// use std::convert::TryFrom;
+/// use diesel_custom_type::CustomSqlType;
+/// use diesel_custom_type::adapters::{Digest32, HexDigest32};
+///
+/// # fn main() {
+/// let digest = Digest32::try_from(&[0xab; 32][..]).unwrap();
+/// assert_eq!(Digest32::from_database_type(&digest.to_database_type()).unwrap(), digest);
+/// assert_eq!(digest.to_string(), "ab".repeat(32));
+/// assert_eq!("AB".repeat(32).parse::<Digest32>().unwrap(), digest);
+///
+/// let err = Digest32::from_database_type(&vec![0; 31]).unwrap_err();
+/// assert_eq!(err.to_string(), "Invalid value `31 bytes` for Digest32: expected exactly 32 bytes");
+///
+/// let hex = HexDigest32(digest);
+/// assert_eq!(hex.to_database_type(), "ab".repeat(32));
+/// assert_eq!(HexDigest32::from_database_type(&"AB".repeat(32)).unwrap(), hex);
+///
+/// let err = HexDigest32::from_database_type(&"zz".repeat(32)).unwrap_err();
+/// assert_eq!(err.to_string(), format!("Invalid value `{}` for Digest32: expected 64 hex digits", "zz".repeat(32)));
+/// assert!(HexDigest32::from_database_type(&"ab".repeat(31)).is_err());
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct Digest32(pub [u8; 32]);
+
+impl Digest32 {
+    /// Returns the digest bytes
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+}
+
+impl<'a> TryFrom<&'a [u8]> for Digest32 {
+    type Error = InvalidValueError;
+
+    fn try_from(v: &'a [u8]) -> Result<Self, InvalidValueError> {
+        if v.len() == 32 {
+            let mut digest = [0; 32];
+            digest.copy_from_slice(v);
+            Ok(Digest32(digest))
+        } else {
+            Err(InvalidValueError::new("Digest32", format!("{} bytes", v.len()), "expected exactly 32 bytes"))
+        }
+    }
+}
+
+impl From<[u8; 32]> for Digest32 {
+    fn from(v: [u8; 32]) -> Self {
+        Digest32(v)
+    }
+}
+
+impl fmt::Display for Digest32 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for b in &self.0 {
+            write!(f, "{:02x}", b)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Digest32 {
+    type Err = InvalidValueError;
+
+    fn from_str(s: &str) -> Result<Self, InvalidValueError> {
+        if s.len() != 64 || !s.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return Err(InvalidValueError::new("Digest32", s, "expected 64 hex digits"));
+        }
+        let mut digest = [0; 32];
+        for (i, b) in digest.iter_mut().enumerate() {
+            *b = u8::from_str_radix(&s[2 * i..2 * i + 2], 16).expect("checked to be hex digits");
+        }
+        Ok(Digest32(digest))
+    }
+}
+
+impl CustomSqlType for Digest32 {
+    type DataBaseType = Binary;
+    type RawType = Vec<u8>;
+    type Error = InvalidValueError;
+
+    fn to_database_type(&self) -> Vec<u8> {
+        self.0.to_vec()
+    }
+
+    fn from_database_type(v: &Vec<u8>) -> Result<Self, InvalidValueError> {
+        Digest32::try_from(&v[..])
+    }
+}
+
+register_custom_type!(Digest32);
+
+/// [`Digest32`](struct.Digest32.html) stored as 64 hex digits in a `Text`
+/// column
+///
+/// Reading is case insensitive, writing always uses lowercase.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct HexDigest32(pub Digest32);
+
+impl Deref for HexDigest32 {
+    type Target = Digest32;
+
+    fn deref(&self) -> &Digest32 {
+        &self.0
+    }
+}
+
+impl From<Digest32> for HexDigest32 {
+    fn from(v: Digest32) -> Self {
+        HexDigest32(v)
+    }
+}
+
+impl CustomSqlType for HexDigest32 {
+    type DataBaseType = Text;
+    type RawType = String;
+    type Error = InvalidValueError;
+
+    fn to_database_type(&self) -> String {
+        self.0.to_string()
+    }
+
+    fn from_database_type(v: &String) -> Result<Self, InvalidValueError> {
+        v.parse().map(HexDigest32)
+    }
+}
+
+register_custom_type!(HexDigest32);
@@ -0,0 +1,148 @@
+use std::error::Error;
+use std::ops::Deref;
+
+use CustomSqlType;
+
+/// Trait indicating how to read a custom type from an older encoding of the
+/// same column
+///
+/// Used by [`DualRead`](struct.DualRead.html) during migrations where old
+/// and new encodings coexist in one column.
+pub trait LegacyEncoding: CustomSqlType {
+    /// How to convert a value written with the old encoding into the custom type
+    fn from_legacy_database_type(v: &Self::RawType) -> Result<Self, Self::Error>;
+
+    /// Called every time a value could only be read using the old encoding
+    ///
+    /// Useful to count how many legacy rows are still read, to know when a
+    /// backfill is complete
+    fn on_legacy_read(_v: &Self::RawType) {}
+}
+
+/// Wrapper accepting both the current and the legacy encoding of a custom type
+///
+/// Reading tries `T::from_database_type` first and falls back to
+/// `T::from_legacy_database_type`. If both fail, the error of the current
+/// encoding is returned. Writing always uses the current encoding.
+///
+/// ```
+/// # extern crate diesel;
+/// # extern crate diesel_custom_type;
+/// # use std::error::Error;
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+/// use diesel::types::Text;
+/// use diesel_custom_type::CustomSqlType;
+/// use diesel_custom_type::adapters::{DualRead, LegacyEncoding};
+///
+/// static LEGACY_READS: AtomicUsize = AtomicUsize::new(0);
+///
+/// #[derive(Debug, Clone, Copy, PartialEq)]
+/// enum Status {
+///     Open,
+///     Closed,
+/// }
+///
+/// impl CustomSqlType for Status {
+///     type DataBaseType = Text;
+///     type RawType = String;
+///     type Error = Box<dyn Error + Send + Sync>;
+///
+///     fn to_database_type(&self) -> String {
+///         match *self {
+///             Status::Open => "open".into(),
+///             Status::Closed => "closed".into(),
+///         }
+///     }
+///
+///     fn from_database_type(v: &String) -> Result<Self, Box<dyn Error + Send + Sync>> {
+///         match &**v {
+///             "open" => Ok(Status::Open),
+///             "closed" => Ok(Status::Closed),
+///             v => Err(format!("Unknown value {} for Status found", v).into()),
+///         }
+///     }
+/// }
+///
+/// impl LegacyEncoding for Status {
+///     fn from_legacy_database_type(v: &String) -> Result<Self, Box<dyn Error + Send + Sync>> {
+///         match &**v {
+///             "1" => Ok(Status::Open),
+///             "2" => Ok(Status::Closed),
+///             v => Err(format!("Unknown legacy value {} for Status found", v).into()),
+///         }
+///     }
+///
+///     fn on_legacy_read(_v: &String) {
+///         LEGACY_READS.fetch_add(1, Ordering::Relaxed);
+///     }
+/// }
+///
+/// # fn main() {
+/// let rows = ["open", "1", "closed", "2", "2"];
+/// let read = rows.iter()
+///     .map(|v| DualRead::<Status>::from_database_type(&v.to_string()).unwrap().0)
+///     .collect::<Vec<_>>();
+/// assert_eq!(read, [Status::Open, Status::Open, Status::Closed, Status::Closed, Status::Closed]);
+/// assert_eq!(LEGACY_READS.load(Ordering::Relaxed), 3);
+///
+/// let written = read.iter().map(|v| DualRead(*v).to_database_type()).collect::<Vec<_>>();
+/// assert_eq!(written, ["open", "open", "closed", "closed", "closed"]);
+///
+/// let err = DualRead::<Status>::from_database_type(&"3".to_string()).unwrap_err();
+/// assert_eq!(err.to_string(), "Unknown value 3 for Status found");
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct DualRead<T>(pub T);
+
+impl<T> DualRead<T> {
+    /// Unwraps the contained value
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> Deref for DualRead<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> From<T> for DualRead<T> {
+    fn from(v: T) -> Self {
+        DualRead(v)
+    }
+}
+
+impl<T> CustomSqlType for DualRead<T>
+    where T: LegacyEncoding
+{
+    type DataBaseType = T::DataBaseType;
+    type RawType = T::RawType;
+    type Error = T::Error;
+
+    fn to_database_type(&self) -> T::RawType {
+        self.0.to_database_type()
+    }
+
+    fn try_to_database_type(&self) -> Result<T::RawType, Box<dyn Error + Send + Sync>> {
+        self.0.try_to_database_type()
+    }
+
+    fn from_database_type(v: &T::RawType) -> Result<Self, T::Error> {
+        match T::from_database_type(v) {
+            Ok(v) => Ok(DualRead(v)),
+            Err(e) => match T::from_legacy_database_type(v) {
+                Ok(legacy) => {
+                    T::on_legacy_read(v);
+                    Ok(DualRead(legacy))
+                }
+                Err(_) => Err(e),
+            },
+        }
+    }
+}
+
+register_custom_type!(impl[T] DualRead<T> where T: LegacyEncoding);
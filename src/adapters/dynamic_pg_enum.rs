@@ -0,0 +1,273 @@
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::fmt;
+use std::io::Write;
+use std::sync::{OnceLock, RwLock};
+
+use diesel::expression::AsExpression;
+use diesel::expression::bound::Bound;
+use diesel::pg::{Pg, PgTypeMetadata};
+use diesel::query_builder::QueryId;
+use diesel::row::Row;
+use diesel::types::{FromSql, FromSqlRow, HasSqlType, IsNull, NotNull, Text, ToSql};
+use diesel::Queryable;
+
+use super::InvalidValueError;
+
+/// SQL type matching any PostgreSQL enum type
+///
+/// Values are sent without a type oid, so PostgreSQL infers the enum type
+/// from the column they are compared with or inserted into.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PgEnum;
+
+impl HasSqlType<PgEnum> for Pg {
+    fn metadata() -> PgTypeMetadata {
+        PgTypeMetadata {
+            oid: 0,
+            array_oid: 0,
+        }
+    }
+}
+
+impl NotNull for PgEnum {}
+
+impl QueryId for PgEnum {
+    type QueryId = Self;
+
+    fn has_static_query_id() -> bool {
+        true
+    }
+}
+
+fn registry() -> &'static RwLock<HashMap<String, HashSet<String>>> {
+    static REGISTRY: OnceLock<RwLock<HashMap<String, HashSet<String>>>> = OnceLock::new();
+    REGISTRY.get_or_init(Default::default)
+}
+
+static UNREGISTERED_LABEL_HOOK: RwLock<Option<fn(&str)>> = RwLock::new(None);
+
+/// Registers the allowed labels of a PostgreSQL enum type created at runtime
+///
+/// Writing a `DynamicPgEnum` of a registered type with any other label
+/// fails before the query is sent, see `DynamicPgEnum::validate`.
+/// Registering a type again replaces its labels.
+pub fn register_pg_enum_labels<I, S>(type_name: &str, labels: I)
+where
+    I: IntoIterator<Item = S>,
+    S: Into<String>,
+{
+    let labels = labels.into_iter().map(Into::into).collect();
+    registry()
+        .write()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(type_name.into(), labels);
+}
+
+/// Sets a function called whenever a label is read that is not registered
+/// for any enum type
+///
+/// Such reads still succeed, as the label exists in the database. The hook
+/// is only called once at least one type was registered.
+pub fn set_unregistered_label_hook(hook: fn(&str)) {
+    *UNREGISTERED_LABEL_HOOK.write().unwrap_or_else(|e| e.into_inner()) = Some(hook);
+}
+
+/// Value of a PostgreSQL enum type that is not known at compile time
+///
+/// Use this for enum types created at runtime, for example per tenant label
+/// sets, together with the `PgEnum` SQL type in `table!`. The enum type name
+/// is only known for values created with `new`; it is used to check the
+/// label against the labels registered with `register_pg_enum_labels`.
+/// Types without registered labels are not checked.
+///
+/// diesel 0.8 panics if a bind parameter can't be serialized, so a query
+/// with an unregistered label panics instead of returning an error. Call
+/// `validate` on values from untrusted input before using them in a query.
+///
+/// ```
+/// # extern crate diesel;
+/// # extern crate diesel_custom_type;
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+/// use diesel::pg::Pg;
+/// use diesel::types::{FromSql, ToSql};
+/// use diesel_custom_type::adapters::{register_pg_enum_labels, set_unregistered_label_hook};
+/// use diesel_custom_type::adapters::{DynamicPgEnum, PgEnum};
+///
+/// static UNREGISTERED: AtomicUsize = AtomicUsize::new(0);
+///
+/// # fn main() {
+/// register_pg_enum_labels("tenant_1_mood", vec!["happy", "sad"]);
+/// set_unregistered_label_hook(|_| { UNREGISTERED.fetch_add(1, Ordering::SeqCst); });
+///
+/// let mut bytes = Vec::new();
+/// ToSql::<PgEnum, Pg>::to_sql(&DynamicPgEnum::new("tenant_1_mood", "happy"), &mut bytes).unwrap();
+/// assert_eq!(bytes, b"happy");
+///
+/// let angry = DynamicPgEnum::new("tenant_1_mood", "angry");
+/// let err = angry.validate().unwrap_err();
+/// assert_eq!(err.to_string(), "Invalid value `angry` for DynamicPgEnum: label is not registered for enum `tenant_1_mood`");
+/// assert!(ToSql::<PgEnum, Pg>::to_sql(&angry, &mut Vec::new()).is_err());
+///
+/// let mood = <DynamicPgEnum as FromSql<PgEnum, Pg>>::from_sql(Some(b"sad")).unwrap();
+/// assert_eq!(mood.label(), "sad");
+/// assert_eq!(UNREGISTERED.load(Ordering::SeqCst), 0);
+///
+/// let mood = <DynamicPgEnum as FromSql<PgEnum, Pg>>::from_sql(Some(b"angry")).unwrap();
+/// assert_eq!(mood.label(), "angry");
+/// assert_eq!(UNREGISTERED.load(Ordering::SeqCst), 1);
+///
+/// // the hook may register the label it is called with
+/// set_unregistered_label_hook(|label| register_pg_enum_labels("tenant_1_new_mood", vec![label]));
+/// <DynamicPgEnum as FromSql<PgEnum, Pg>>::from_sql(Some(b"bored")).unwrap();
+/// assert!(DynamicPgEnum::new("tenant_1_new_mood", "bored").validate().is_ok());
+/// assert!(DynamicPgEnum::new("tenant_1_new_mood", "angry").validate().is_err());
+/// # }
+/// ```
+///
+/// Round trip through a live database:
+///
+/// ```no_run
+/// # #[macro_use] extern crate diesel;
+/// # extern crate diesel_custom_type;
+/// use diesel::prelude::*;
+/// use diesel::pg::PgConnection;
+/// use diesel_custom_type::adapters::{register_pg_enum_labels, DynamicPgEnum};
+///
+/// table! {
+///     moods {
+///         id -> Integer,
+///         mood -> ::diesel_custom_type::adapters::PgEnum,
+///     }
+/// }
+///
+/// # fn main() {
+/// let conn = PgConnection::establish(&::std::env::var("DATABASE_URL").unwrap()).unwrap();
+/// conn.begin_test_transaction().unwrap();
+/// conn.execute("CREATE TYPE tenant_2_mood AS ENUM ('happy', 'sad')").unwrap();
+/// conn.execute("CREATE TEMPORARY TABLE moods (id SERIAL PRIMARY KEY, mood tenant_2_mood NOT NULL)").unwrap();
+/// register_pg_enum_labels("tenant_2_mood", vec!["happy", "sad"]);
+///
+/// conn.execute("INSERT INTO moods (mood) VALUES ('happy'), ('happy')").unwrap();
+/// let sad = DynamicPgEnum::new("tenant_2_mood", "sad");
+/// diesel::update(moods::table.find(2)).set(moods::mood.eq(&sad)).execute(&conn).unwrap();
+/// let ids = moods::table.filter(moods::mood.eq(&sad)).select(moods::id).load::<i32>(&conn).unwrap();
+/// assert_eq!(ids, [2]);
+/// let read = moods::table.select(moods::mood).order(moods::id).load::<DynamicPgEnum>(&conn).unwrap();
+/// assert_eq!(read.iter().map(DynamicPgEnum::label).collect::<Vec<_>>(), ["happy", "sad"]);
+///
+/// // rejected before it reaches a query, which would panic
+/// let angry = DynamicPgEnum::new("tenant_2_mood", "angry");
+/// assert!(angry.validate().is_err());
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DynamicPgEnum {
+    type_name: Option<String>,
+    label: String,
+}
+
+impl DynamicPgEnum {
+    /// Creates a value of the enum type `type_name`
+    pub fn new<T: Into<String>, L: Into<String>>(type_name: T, label: L) -> Self {
+        DynamicPgEnum {
+            type_name: Some(type_name.into()),
+            label: label.into(),
+        }
+    }
+
+    /// The enum label
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    /// The enum type name, `None` for values read from the database
+    pub fn type_name(&self) -> Option<&str> {
+        self.type_name.as_deref()
+    }
+
+    /// Checks the label against the labels registered for the enum type
+    ///
+    /// Succeeds for values read from the database and for types without
+    /// registered labels. Writing a value fails with the same error.
+    pub fn validate(&self) -> Result<(), InvalidValueError> {
+        let type_name = match self.type_name {
+            Some(ref type_name) => type_name,
+            None => return Ok(()),
+        };
+        let registry = registry().read().unwrap_or_else(|e| e.into_inner());
+        match registry.get(type_name) {
+            Some(labels) if !labels.contains(&self.label) => {
+                let reason = format!("label is not registered for enum `{}`", type_name);
+                Err(InvalidValueError::new("DynamicPgEnum", &self.label, reason))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+impl fmt::Display for DynamicPgEnum {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.label)
+    }
+}
+
+// Implemented by hand as there is no `String` conversion for `PgEnum`; the
+// wire format of enum values is their label, the same as for `Text`.
+impl ToSql<PgEnum, Pg> for DynamicPgEnum {
+    fn to_sql<W: Write>(&self, out: &mut W) -> Result<IsNull, Box<dyn Error + Send + Sync>> {
+        self.validate()?;
+        ToSql::<Text, Pg>::to_sql(&self.label, out)
+    }
+}
+
+impl FromSql<PgEnum, Pg> for DynamicPgEnum {
+    fn from_sql(bytes: Option<&[u8]>) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        let label = <String as FromSql<Text, Pg>>::from_sql(bytes)?;
+        let unregistered = {
+            let registry = registry().read().unwrap_or_else(|e| e.into_inner());
+            !registry.is_empty() && !registry.values().any(|labels| labels.contains(&label))
+        };
+        // called without holding any lock, so the hook can register labels
+        if unregistered {
+            let hook = *UNREGISTERED_LABEL_HOOK.read().unwrap_or_else(|e| e.into_inner());
+            if let Some(hook) = hook {
+                hook(&label);
+            }
+        }
+        Ok(DynamicPgEnum {
+            type_name: None,
+            label,
+        })
+    }
+}
+
+impl FromSqlRow<PgEnum, Pg> for DynamicPgEnum {
+    fn build_from_row<R: Row<Pg>>(row: &mut R) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        DynamicPgEnum::from_sql(row.take())
+    }
+}
+
+impl Queryable<PgEnum, Pg> for DynamicPgEnum {
+    type Row = Self;
+
+    fn build(row: Self) -> Self {
+        row
+    }
+}
+
+impl AsExpression<PgEnum> for DynamicPgEnum {
+    type Expression = Bound<PgEnum, DynamicPgEnum>;
+
+    fn as_expression(self) -> Self::Expression {
+        Bound::new(self)
+    }
+}
+
+impl<'a> AsExpression<PgEnum> for &'a DynamicPgEnum {
+    type Expression = Bound<PgEnum, &'a DynamicPgEnum>;
+
+    fn as_expression(self) -> Self::Expression {
+        Bound::new(self)
+    }
+}
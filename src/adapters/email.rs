@@ -0,0 +1,95 @@
+use std::fmt;
+use std::str::FromStr;
+
+use diesel::types::Text;
+
+use CustomSqlType;
+use super::InvalidValueError;
+
+/// Email address stored in a `Text` column
+///
+/// Validation is pragmatic instead of following RFC 5322: an address must
+/// contain exactly one `@` with a non-empty local part and domain, and no
+/// whitespace. The domain is lowercased, the local part is kept as is.
+///
+/// ```
+/// # extern crate diesel;
+/// # extern crate diesel_custom_type;
+/// use diesel_custom_type::CustomSqlType;
+/// use diesel_custom_type::adapters::Email;
+///
+/// # fn main() {
+/// let email = "Jane.Doe@Example.COM".parse::<Email>().unwrap();
+/// assert_eq!(email.local_part(), "Jane.Doe");
+/// assert_eq!(email.domain(), "example.com");
+/// assert_eq!(email.to_database_type(), "Jane.Doe@example.com");
+/// assert_eq!(Email::from_database_type(&email.to_database_type()).unwrap(), email);
+///
+/// let err = Email::from_database_type(&"jane doe@example.com".to_string()).unwrap_err();
+/// assert_eq!(err.to_string(), "Invalid value `jane doe@example.com` for Email: whitespace is not allowed");
+/// assert!("jane@".parse::<Email>().is_err());
+/// assert!("a@b@example.com".parse::<Email>().is_err());
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Email {
+    address: String,
+    at: usize,
+}
+
+impl Email {
+    /// Returns the whole address
+    pub fn as_str(&self) -> &str {
+        &self.address
+    }
+
+    /// Returns the part before the `@`
+    pub fn local_part(&self) -> &str {
+        &self.address[..self.at]
+    }
+
+    /// Returns the lowercased part after the `@`
+    pub fn domain(&self) -> &str {
+        &self.address[self.at + 1..]
+    }
+}
+
+impl fmt::Display for Email {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.address)
+    }
+}
+
+impl FromStr for Email {
+    type Err = InvalidValueError;
+
+    fn from_str(s: &str) -> Result<Self, InvalidValueError> {
+        if s.chars().any(char::is_whitespace) {
+            return Err(InvalidValueError::new("Email", s, "whitespace is not allowed"));
+        }
+        let mut parts = s.split('@');
+        match (parts.next(), parts.next(), parts.next()) {
+            (Some(local), Some(domain), None) if !local.is_empty() && !domain.is_empty() => Ok(Email {
+                address: format!("{}@{}", local, domain.to_lowercase()),
+                at: local.len(),
+            }),
+            _ => Err(InvalidValueError::new("Email", s, "expected exactly one `@` between local part and domain")),
+        }
+    }
+}
+
+impl CustomSqlType for Email {
+    type DataBaseType = Text;
+    type RawType = String;
+    type Error = InvalidValueError;
+
+    fn to_database_type(&self) -> String {
+        self.address.clone()
+    }
+
+    fn from_database_type(v: &String) -> Result<Self, InvalidValueError> {
+        v.parse()
+    }
+}
+
+register_custom_type!(Email);
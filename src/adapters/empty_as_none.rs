@@ -0,0 +1,125 @@
+use std::error::Error;
+use std::ops::Deref;
+
+use diesel::types::{Nullable, Text};
+
+use CustomSqlType;
+
+/// Wrapper for text backed custom types stored in a `Nullable<Text>` column
+/// where an empty string means the same as `NULL`
+///
+/// Reading `NULL` or an empty string yields `EmptyAsNone(None)`, every other
+/// value is converted using `T::from_database_type`. Writing
+/// `EmptyAsNone(None)` always produces `NULL`.
+///
+/// Set `TRIM` to `true` (or use [`BlankAsNone`](type.BlankAsNone.html)) to
+/// treat strings consisting only of whitespace as `NULL` as well.
+///
+/// ```
+/// # extern crate diesel;
+/// # extern crate diesel_custom_type;
+/// # use std::error::Error;
+/// use diesel::pg::Pg;
+/// use diesel::types::{IsNull, Nullable, Text, ToSql};
+/// use diesel_custom_type::CustomSqlType;
+/// use diesel_custom_type::adapters::{BlankAsNone, EmptyAsNone};
+///
+/// #[derive(Debug, PartialEq)]
+/// struct Name(String);
+///
+/// impl CustomSqlType for Name {
+///     type DataBaseType = Text;
+///     type RawType = String;
+///     type Error = Box<dyn Error + Send + Sync>;
+///
+///     fn to_database_type(&self) -> String {
+///         self.0.clone()
+///     }
+///
+///     fn from_database_type(v: &String) -> Result<Self, Box<dyn Error + Send + Sync>> {
+///         Ok(Name(v.clone()))
+///     }
+/// }
+///
+/// # fn main() {
+/// let empty = Some(String::new());
+/// assert_eq!(EmptyAsNone::<Name>::from_database_type(&empty).unwrap(), EmptyAsNone(None));
+///
+/// let blank = Some(String::from("  "));
+/// assert_eq!(EmptyAsNone::<Name>::from_database_type(&blank).unwrap(),
+///            EmptyAsNone(Some(Name(String::from("  ")))));
+/// assert_eq!(BlankAsNone::<Name>::from_database_type(&blank).unwrap(), EmptyAsNone(None));
+///
+/// let value = Some(String::from("Sean"));
+/// assert_eq!(BlankAsNone::<Name>::from_database_type(&value).unwrap(),
+///            EmptyAsNone(Some(Name(String::from("Sean")))));
+///
+/// let mut bytes = Vec::new();
+/// let is_null = ToSql::<Nullable<Text>, Pg>::to_sql(&EmptyAsNone::<Name>(None), &mut bytes).unwrap();
+/// assert_eq!(is_null, IsNull::Yes);
+/// assert!(bytes.is_empty());
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct EmptyAsNone<T, const TRIM: bool = false>(pub Option<T>);
+
+/// `EmptyAsNone` treating all-whitespace strings as `NULL`
+pub type BlankAsNone<T> = EmptyAsNone<T, true>;
+
+impl<T, const TRIM: bool> EmptyAsNone<T, TRIM> {
+    /// Unwraps the contained optional value
+    pub fn into_inner(self) -> Option<T> {
+        self.0
+    }
+
+    fn is_none_value(v: &str) -> bool {
+        if TRIM {
+            v.trim().is_empty()
+        } else {
+            v.is_empty()
+        }
+    }
+}
+
+impl<T, const TRIM: bool> Deref for EmptyAsNone<T, TRIM> {
+    type Target = Option<T>;
+
+    fn deref(&self) -> &Option<T> {
+        &self.0
+    }
+}
+
+impl<T, const TRIM: bool> From<Option<T>> for EmptyAsNone<T, TRIM> {
+    fn from(v: Option<T>) -> Self {
+        EmptyAsNone(v)
+    }
+}
+
+impl<T, const TRIM: bool> CustomSqlType for EmptyAsNone<T, TRIM>
+    where T: CustomSqlType<DataBaseType = Text, RawType = String>
+{
+    type DataBaseType = Nullable<Text>;
+    type RawType = Option<String>;
+    type Error = T::Error;
+
+    fn to_database_type(&self) -> Option<String> {
+        self.0.as_ref().map(T::to_database_type)
+    }
+
+    fn try_to_database_type(&self) -> Result<Option<String>, Box<dyn Error + Send + Sync>> {
+        match self.0 {
+            Some(ref v) => v.try_to_database_type().map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn from_database_type(v: &Option<String>) -> Result<Self, T::Error> {
+        match *v {
+            Some(ref v) if !Self::is_none_value(v) => T::from_database_type(v).map(|v| EmptyAsNone(Some(v))),
+            _ => Ok(EmptyAsNone(None)),
+        }
+    }
+}
+
+register_custom_type!(impl[T, const TRIM: bool] EmptyAsNone<T, TRIM>
+                      where T: CustomSqlType<DataBaseType = Text, RawType = String>);
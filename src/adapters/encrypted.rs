@@ -0,0 +1,212 @@
+use std::fmt;
+use std::marker::PhantomData;
+use std::ops::Deref;
+
+use diesel::types::{Binary, Text};
+
+use CustomSqlType;
+use super::RedactedValueError;
+use super::base64;
+
+/// Application provided encryption used by [`Encrypted`](struct.Encrypted.html)
+///
+/// This crate does not implement any cryptography itself.
+pub trait CipherProvider {
+    /// Encrypts `plaintext`
+    fn encrypt(plaintext: &[u8]) -> Vec<u8>;
+
+    /// Decrypts `ciphertext`, returns `None` if that is not possible (wrong
+    /// key, tampered data, ...)
+    fn decrypt(ciphertext: &[u8]) -> Option<Vec<u8>>;
+}
+
+/// Values that can be encrypted by [`Encrypted`](struct.Encrypted.html)
+pub trait Plaintext: Sized {
+    /// Returns the bytes to encrypt
+    fn to_plaintext(&self) -> Vec<u8>;
+
+    /// Reconstructs the value from decrypted bytes
+    fn from_plaintext(bytes: Vec<u8>) -> Option<Self>;
+}
+
+impl Plaintext for String {
+    fn to_plaintext(&self) -> Vec<u8> {
+        self.as_bytes().to_vec()
+    }
+
+    fn from_plaintext(bytes: Vec<u8>) -> Option<Self> {
+        String::from_utf8(bytes).ok()
+    }
+}
+
+impl Plaintext for Vec<u8> {
+    fn to_plaintext(&self) -> Vec<u8> {
+        self.clone()
+    }
+
+    fn from_plaintext(bytes: Vec<u8>) -> Option<Self> {
+        Some(bytes)
+    }
+}
+
+/// Column types able to hold the ciphertext of an
+/// [`Encrypted`](struct.Encrypted.html) value
+///
+/// Implemented for `Binary` (raw bytes) and `Text` (base64)
+pub trait CiphertextStorage {
+    /// Raw rust type corresponding to the diesel type
+    type RawType;
+
+    /// Converts the ciphertext into the stored representation
+    fn store(ciphertext: Vec<u8>) -> Self::RawType;
+
+    /// Extracts the ciphertext from the stored representation
+    fn load(v: &Self::RawType) -> Option<Vec<u8>>;
+}
+
+impl CiphertextStorage for Binary {
+    type RawType = Vec<u8>;
+
+    fn store(ciphertext: Vec<u8>) -> Vec<u8> {
+        ciphertext
+    }
+
+    fn load(v: &Vec<u8>) -> Option<Vec<u8>> {
+        Some(v.clone())
+    }
+}
+
+impl CiphertextStorage for Text {
+    type RawType = String;
+
+    fn store(ciphertext: Vec<u8>) -> String {
+        base64::encode(&ciphertext, true)
+    }
+
+    fn load(v: &String) -> Option<Vec<u8>> {
+        base64::decode(v).ok()
+    }
+}
+
+/// Value encrypted at the application layer before it is written
+///
+/// Encryption is done by the `CipherProvider` `C`, the ciphertext is stored in
+/// a column of type `S`, either `Binary` (default) or `Text` (base64 encoded).
+/// Values that can not be decrypted result in a
+/// [`RedactedValueError`](struct.RedactedValueError.html), which contains
+/// neither the ciphertext nor the plaintext. `Debug` never prints the value.
+///
+/// ```
+/// # extern crate diesel;
+/// # extern crate diesel_custom_type;
+/// use diesel::types::Text;
+/// use diesel_custom_type::CustomSqlType;
+/// use diesel_custom_type::adapters::{CipherProvider, Encrypted};
+///
+/// // Toy cipher for demonstration purposes only: xor with a key byte and
+/// // append a checksum
+/// struct Xor;
+///
+/// impl CipherProvider for Xor {
+///     fn encrypt(plaintext: &[u8]) -> Vec<u8> {
+///         let mut out = plaintext.iter().map(|b| b ^ 0x5a).collect::<Vec<_>>();
+///         out.push(plaintext.iter().fold(0, |a: u8, b| a.wrapping_add(*b)));
+///         out
+///     }
+///
+///     fn decrypt(ciphertext: &[u8]) -> Option<Vec<u8>> {
+///         let (checksum, data) = ciphertext.split_last()?;
+///         let plaintext = data.iter().map(|b| b ^ 0x5a).collect::<Vec<_>>();
+///         if plaintext.iter().fold(0, |a: u8, b| a.wrapping_add(*b)) == *checksum {
+///             Some(plaintext)
+///         } else {
+///             None
+///         }
+///     }
+/// }
+///
+/// # fn main() {
+/// let ssn = Encrypted::<String, Xor>::new("078-05-1120".to_string());
+/// assert_eq!(format!("{:?}", ssn), "Encrypted([REDACTED])");
+///
+/// let stored = ssn.to_database_type();
+/// assert_ne!(stored, b"078-05-1120".to_vec());
+/// let read = Encrypted::<String, Xor>::from_database_type(&stored).unwrap();
+/// assert_eq!(&*read, "078-05-1120");
+///
+/// let mut tampered = stored.clone();
+/// tampered[0] ^= 1;
+/// let err = Encrypted::<String, Xor>::from_database_type(&tampered).unwrap_err();
+/// assert!(!err.to_string().contains("078"));
+///
+/// let text = Encrypted::<String, Xor, Text>::new("078-05-1120".to_string());
+/// let stored = text.to_database_type();
+/// assert_eq!(&*Encrypted::<String, Xor, Text>::from_database_type(&stored).unwrap(), "078-05-1120");
+/// # }
+/// ```
+pub struct Encrypted<T, C, S = Binary> {
+    value: T,
+    cipher: PhantomData<fn() -> (C, S)>,
+}
+
+impl<T, C, S> Encrypted<T, C, S> {
+    /// Wraps a plaintext value
+    pub fn new(value: T) -> Self {
+        Encrypted {
+            value,
+            cipher: PhantomData,
+        }
+    }
+
+    /// Unwraps the plaintext value
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+impl<T, C, S> Deref for Encrypted<T, C, S> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<T: Clone, C, S> Clone for Encrypted<T, C, S> {
+    fn clone(&self) -> Self {
+        Encrypted::new(self.value.clone())
+    }
+}
+
+impl<T, C, S> fmt::Debug for Encrypted<T, C, S> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("Encrypted([REDACTED])")
+    }
+}
+
+impl<T, C, S> CustomSqlType for Encrypted<T, C, S>
+    where T: Plaintext,
+          C: CipherProvider,
+          S: CiphertextStorage
+{
+    type DataBaseType = S;
+    type RawType = S::RawType;
+    type Error = RedactedValueError;
+
+    fn to_database_type(&self) -> S::RawType {
+        S::store(C::encrypt(&self.value.to_plaintext()))
+    }
+
+    fn from_database_type(v: &S::RawType) -> Result<Self, RedactedValueError> {
+        S::load(v)
+            .and_then(|ciphertext| C::decrypt(&ciphertext))
+            .and_then(T::from_plaintext)
+            .map(Encrypted::new)
+            .ok_or(RedactedValueError)
+    }
+}
+
+register_custom_type!(impl[T, C, S] Encrypted<T, C, S>
+                      where T: Plaintext,
+                            C: CipherProvider,
+                            S: CiphertextStorage);
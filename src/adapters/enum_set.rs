@@ -0,0 +1,285 @@
+use std::error::Error;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+
+use diesel::types::BigInt;
+
+use super::InvalidValueError;
+use {CustomSqlType, Variants};
+
+/// Highest number of variants that fit into the bits of a `BigInt` column
+/// without using the sign bit
+const MAX_VARIANTS: usize = 63;
+
+/// Set of enum values stored as a bitmask in a `BigInt` column
+///
+/// Bit `i` is set if the `i`-th value returned by `T::variants()` is part of
+/// the set, so new variants must be added at the end of that list. Types
+/// with more than 63 variants can't be stored, inserting, reading or writing
+/// them fails.
+///
+/// Reading a bitmask with bits set that don't belong to any variant fails,
+/// set `IGNORE_UNKNOWN` to `true` (or use
+/// [`LenientEnumSet`](type.LenientEnumSet.html)) to drop them instead.
+///
+/// ```
+/// # extern crate diesel_custom_type;
+/// use diesel_custom_type::{CustomSqlType, Variants};
+/// use diesel_custom_type::adapters::{EnumSet, LenientEnumSet};
+///
+/// #[derive(Debug, Clone, Copy, PartialEq)]
+/// enum Feature {
+///     Search,
+///     Export,
+///     Sharing,
+/// }
+///
+/// impl Variants for Feature {
+///     fn variants() -> &'static [Self] {
+///         &[Feature::Search, Feature::Export, Feature::Sharing]
+///     }
+/// }
+///
+/// # fn main() {
+/// let mut features: EnumSet<Feature> = EnumSet::new();
+/// features.insert(Feature::Search).unwrap();
+/// features.insert(Feature::Sharing).unwrap();
+/// assert!(features.contains(&Feature::Sharing));
+/// assert!(!features.contains(&Feature::Export));
+/// assert_eq!(features.to_database_type(), 0b101);
+///
+/// assert!(features.remove(&Feature::Search));
+/// assert_eq!(features.iter().collect::<Vec<_>>(), [Feature::Sharing]);
+///
+/// let all = EnumSet::<Feature>::try_from_iter(Feature::variants().iter().cloned()).unwrap();
+/// assert_eq!(all.len(), 3);
+///
+/// let read = EnumSet::<Feature>::from_database_type(&0b011).unwrap();
+/// assert_eq!(read.iter().collect::<Vec<_>>(), [Feature::Search, Feature::Export]);
+///
+/// let err = EnumSet::<Feature>::from_database_type(&0b1001).unwrap_err();
+/// assert_eq!(err.to_string(), "Invalid value `9` for EnumSet: bits without a variant are set");
+/// let read = LenientEnumSet::<Feature>::from_database_type(&0b1001).unwrap();
+/// assert_eq!(read.iter().collect::<Vec<_>>(), [Feature::Search]);
+/// # }
+/// ```
+///
+/// Types with too many variants are rejected:
+///
+/// ```
+/// # extern crate diesel_custom_type;
+/// use diesel_custom_type::{CustomSqlType, Variants};
+/// use diesel_custom_type::adapters::EnumSet;
+///
+/// #[derive(Debug, Clone, Copy, PartialEq)]
+/// struct Bit(u8);
+///
+/// static BITS: [Bit; 64] = {
+///     let mut bits = [Bit(0); 64];
+///     let mut i = 0;
+///     while i < 64 {
+///         bits[i] = Bit(i as u8);
+///         i += 1;
+///     }
+///     bits
+/// };
+///
+/// impl Variants for Bit {
+///     fn variants() -> &'static [Self] {
+///         &BITS
+///     }
+/// }
+///
+/// # fn main() {
+/// let err = EnumSet::<Bit>::from_database_type(&1).unwrap_err();
+/// assert_eq!(err.to_string(), "Invalid value `64` for EnumSet: more than 63 variants");
+/// assert!(EnumSet::<Bit>::new().try_to_database_type().is_err());
+///
+/// let mut set = EnumSet::<Bit>::new();
+/// assert!(set.insert(Bit(0)).is_err());
+/// assert!(!set.contains(&Bit(0)));
+/// assert!(EnumSet::<Bit>::try_from_iter(vec![Bit(1)]).is_err());
+/// # }
+/// ```
+pub struct EnumSet<T, const IGNORE_UNKNOWN: bool = false> {
+    bits: u64,
+    variants: PhantomData<fn() -> T>,
+}
+
+/// `EnumSet` dropping unknown bits while reading
+pub type LenientEnumSet<T> = EnumSet<T, true>;
+
+impl<T: Variants + PartialEq, const IGNORE_UNKNOWN: bool> EnumSet<T, IGNORE_UNKNOWN> {
+    /// Creates an empty set
+    pub fn new() -> Self {
+        EnumSet {
+            bits: 0,
+            variants: PhantomData,
+        }
+    }
+
+    /// Creates a set of the values of `iter`, fails like `insert`
+    pub fn try_from_iter<I: IntoIterator<Item = T>>(iter: I) -> Result<Self, InvalidValueError> {
+        let mut set = EnumSet::new();
+        for v in iter {
+            set.insert(v)?;
+        }
+        Ok(set)
+    }
+
+    // `None` if the type has too many variants, or if `v` is missing from
+    // `T::variants()`
+    fn bit(v: &T) -> Option<u64> {
+        if Self::check_variant_count().is_err() {
+            return None;
+        }
+        T::variants().iter().position(|o| o == v).map(|index| 1 << index)
+    }
+
+    fn check_variant_count() -> Result<(), InvalidValueError> {
+        let count = T::variants().len();
+        if count > MAX_VARIANTS {
+            Err(InvalidValueError::new("EnumSet", count, format!("more than {} variants", MAX_VARIANTS)))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Returns true if `v` is part of the set
+    pub fn contains(&self, v: &T) -> bool {
+        Self::bit(v).is_some_and(|bit| self.bits & bit != 0)
+    }
+
+    /// Adds `v` to the set, returns false if it was already present
+    ///
+    /// Fails if `T` has more than 63 variants or if `v` is not listed by
+    /// `T::variants()`.
+    pub fn insert(&mut self, v: T) -> Result<bool, InvalidValueError> {
+        Self::check_variant_count()?;
+        let bit = Self::bit(&v).ok_or_else(|| {
+            InvalidValueError::new("EnumSet", T::variants().len(), "inserted value is not listed by `Variants::variants()`")
+        })?;
+        let added = self.bits & bit == 0;
+        self.bits |= bit;
+        Ok(added)
+    }
+
+    /// Removes `v` from the set, returns false if it wasn't present
+    pub fn remove(&mut self, v: &T) -> bool {
+        match Self::bit(v) {
+            Some(bit) => {
+                let removed = self.bits & bit != 0;
+                self.bits &= !bit;
+                removed
+            }
+            None => false,
+        }
+    }
+
+    /// Iterates over the values of the set, in the order of `T::variants()`
+    pub fn iter(&self) -> EnumSetIter<T> {
+        EnumSetIter {
+            bits: self.bits,
+            variants: T::variants().iter(),
+        }
+    }
+
+    /// Number of values in the set
+    pub fn len(&self) -> usize {
+        self.bits.count_ones() as usize
+    }
+
+    /// Returns true if the set contains no values
+    pub fn is_empty(&self) -> bool {
+        self.bits == 0
+    }
+}
+
+/// Iterator over the values of an `EnumSet`
+pub struct EnumSetIter<T: 'static> {
+    bits: u64,
+    variants: ::std::slice::Iter<'static, T>,
+}
+
+impl<T: Copy + 'static> Iterator for EnumSetIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        for v in &mut self.variants {
+            let present = self.bits & 1 != 0;
+            self.bits >>= 1;
+            if present {
+                return Some(*v);
+            }
+        }
+        None
+    }
+}
+
+impl<T: Variants + PartialEq, const IGNORE_UNKNOWN: bool> Default for EnumSet<T, IGNORE_UNKNOWN> {
+    fn default() -> Self {
+        EnumSet::new()
+    }
+}
+
+impl<T, const IGNORE_UNKNOWN: bool> Clone for EnumSet<T, IGNORE_UNKNOWN> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T, const IGNORE_UNKNOWN: bool> Copy for EnumSet<T, IGNORE_UNKNOWN> {}
+
+impl<T, const IGNORE_UNKNOWN: bool> PartialEq for EnumSet<T, IGNORE_UNKNOWN> {
+    fn eq(&self, other: &Self) -> bool {
+        self.bits == other.bits
+    }
+}
+
+impl<T, const IGNORE_UNKNOWN: bool> Eq for EnumSet<T, IGNORE_UNKNOWN> {}
+
+impl<T, const IGNORE_UNKNOWN: bool> Hash for EnumSet<T, IGNORE_UNKNOWN> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.bits.hash(state)
+    }
+}
+
+impl<T, const IGNORE_UNKNOWN: bool> fmt::Debug for EnumSet<T, IGNORE_UNKNOWN>
+    where T: Variants + PartialEq + Copy + fmt::Debug
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl<T: Variants + PartialEq, const IGNORE_UNKNOWN: bool> CustomSqlType for EnumSet<T, IGNORE_UNKNOWN> {
+    type DataBaseType = BigInt;
+    type RawType = i64;
+    type Error = InvalidValueError;
+
+    fn to_database_type(&self) -> i64 {
+        self.bits as i64
+    }
+
+    fn try_to_database_type(&self) -> Result<i64, Box<dyn Error + Send + Sync>> {
+        Self::check_variant_count()?;
+        Ok(self.to_database_type())
+    }
+
+    fn from_database_type(v: &i64) -> Result<Self, InvalidValueError> {
+        Self::check_variant_count()?;
+        let known = (1u64 << T::variants().len()) - 1;
+        let bits = *v as u64;
+        if bits & !known != 0 && !IGNORE_UNKNOWN {
+            return Err(InvalidValueError::new("EnumSet", v, "bits without a variant are set"));
+        }
+        Ok(EnumSet {
+            bits: bits & known,
+            variants: PhantomData,
+        })
+    }
+}
+
+register_custom_type!(impl[T, const IGNORE_UNKNOWN: bool] EnumSet<T, IGNORE_UNKNOWN>
+                      where T: Variants + PartialEq);
@@ -0,0 +1,37 @@
+use std::error::Error;
+use std::fmt;
+
+/// Error returned if a value read from the database is not valid for an
+/// adapter type
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidValueError {
+    /// Name of the adapter type
+    pub type_name: &'static str,
+    /// The rejected value as found in the database
+    pub value: String,
+    /// Why the value was rejected
+    pub reason: String,
+}
+
+impl InvalidValueError {
+    /// Creates a new error for the value `value` of the type `type_name`
+    pub fn new<V: fmt::Display, R: Into<String>>(type_name: &'static str, value: V, reason: R) -> Self {
+        InvalidValueError {
+            type_name,
+            value: value.to_string(),
+            reason: reason.into(),
+        }
+    }
+}
+
+impl fmt::Display for InvalidValueError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Invalid value `{}` for {}: {}", self.value, self.type_name, self.reason)
+    }
+}
+
+impl Error for InvalidValueError {
+    fn description(&self) -> &str {
+        &self.reason
+    }
+}
@@ -0,0 +1,267 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::io::Write;
+use std::ops::{Deref, DerefMut};
+
+use diesel::expression::AsExpression;
+use diesel::expression::bound::Bound;
+use diesel::pg::{Pg, PgTypeMetadata};
+use diesel::query_builder::QueryId;
+use diesel::row::Row;
+use diesel::types::{Binary, FromSql, FromSqlRow, HasSqlType, IsNull, NotNull, ToSql};
+use diesel::Queryable;
+
+use super::InvalidValueError;
+
+/// SQL type of PostgreSQL `hstore` columns
+///
+/// `hstore` is provided by an extension and has no fixed oid, values are
+/// sent without one and PostgreSQL infers the type from the column.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HStore;
+
+impl HasSqlType<HStore> for Pg {
+    fn metadata() -> PgTypeMetadata {
+        PgTypeMetadata {
+            oid: 0,
+            array_oid: 0,
+        }
+    }
+}
+
+impl NotNull for HStore {}
+
+impl QueryId for HStore {
+    type QueryId = Self;
+
+    fn has_static_query_id() -> bool {
+        true
+    }
+}
+
+/// Map stored in a PostgreSQL `hstore` column
+///
+/// `NULL` values inside the hstore are read as `None`. Keys and values are
+/// sent in the binary format, so they may contain any character.
+///
+/// ```
+/// # extern crate diesel;
+/// # extern crate diesel_custom_type;
+/// use diesel::pg::Pg;
+/// use diesel::types::{FromSql, ToSql};
+/// use diesel_custom_type::adapters::{HStore, HStoreMap};
+///
+/// # fn main() {
+/// let mut properties = HStoreMap::default();
+/// properties.insert(r#"say "hi", a=>b"#.to_string(), Some("1".to_string()));
+/// properties.insert("deleted".to_string(), None);
+///
+/// let mut bytes = Vec::new();
+/// ToSql::<HStore, Pg>::to_sql(&properties, &mut bytes).unwrap();
+/// let read = <HStoreMap as FromSql<HStore, Pg>>::from_sql(Some(&bytes)).unwrap();
+/// assert_eq!(read, properties);
+///
+/// let err = <HStoreMap as FromSql<HStore, Pg>>::from_sql(Some(&bytes[..bytes.len() - 1])).unwrap_err();
+/// assert_eq!(err.to_string(), "Invalid value `hstore` for HStoreMap: unexpected end of data");
+///
+/// let err = <HStoreMap as FromSql<HStore, Pg>>::from_sql(Some(&i32::MAX.to_be_bytes())).unwrap_err();
+/// assert_eq!(err.to_string(), "Invalid value `hstore` for HStoreMap: unexpected end of data");
+/// # }
+/// ```
+///
+/// Round trip through a live database with the `hstore` extension:
+///
+/// ```no_run
+/// # #[macro_use] extern crate diesel;
+/// # extern crate diesel_custom_type;
+/// use diesel::prelude::*;
+/// use diesel::pg::PgConnection;
+/// use diesel_custom_type::adapters::HStoreMap;
+///
+/// table! {
+///     settings {
+///         id -> Integer,
+///         properties -> ::diesel_custom_type::adapters::HStore,
+///     }
+/// }
+///
+/// struct NewSetting {
+///     properties: HStoreMap,
+/// }
+///
+/// Insertable! {
+///     (settings)
+///     struct NewSetting {
+///         properties: HStoreMap,
+///     }
+/// }
+///
+/// # fn main() {
+/// let conn = PgConnection::establish(&::std::env::var("DATABASE_URL").unwrap()).unwrap();
+/// conn.execute("CREATE EXTENSION IF NOT EXISTS hstore").unwrap();
+/// conn.execute("CREATE TEMPORARY TABLE settings (id SERIAL PRIMARY KEY, properties hstore NOT NULL)").unwrap();
+///
+/// let mut properties = HStoreMap::default();
+/// properties.insert(r#"a"b"#.to_string(), Some(r#"say "hi""#.to_string()));
+/// properties.insert("x,y=>z".to_string(), None);
+/// properties.insert(r"back\slash".to_string(), Some("=>".to_string()));
+/// let new_setting = NewSetting { properties: properties.clone() };
+/// diesel::insert(&new_setting).into(settings::table).execute(&conn).unwrap();
+///
+/// let read = settings::table.select(settings::properties).first::<HStoreMap>(&conn).unwrap();
+/// assert_eq!(read, properties);
+/// let id = settings::table.filter(settings::properties.eq(&properties)).select(settings::id).first::<i32>(&conn).unwrap();
+/// assert_eq!(id, 1);
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct HStoreMap(pub HashMap<String, Option<String>>);
+
+impl HStoreMap {
+    /// Unwraps the contained map
+    pub fn into_inner(self) -> HashMap<String, Option<String>> {
+        self.0
+    }
+}
+
+impl Deref for HStoreMap {
+    type Target = HashMap<String, Option<String>>;
+
+    fn deref(&self) -> &HashMap<String, Option<String>> {
+        &self.0
+    }
+}
+
+impl DerefMut for HStoreMap {
+    fn deref_mut(&mut self) -> &mut HashMap<String, Option<String>> {
+        &mut self.0
+    }
+}
+
+impl From<HashMap<String, Option<String>>> for HStoreMap {
+    fn from(v: HashMap<String, Option<String>>) -> Self {
+        HStoreMap(v)
+    }
+}
+
+impl From<HStoreMap> for HashMap<String, Option<String>> {
+    fn from(v: HStoreMap) -> Self {
+        v.0
+    }
+}
+
+fn invalid(reason: &str) -> Box<dyn Error + Send + Sync> {
+    Box::new(InvalidValueError::new("HStoreMap", "hstore", reason))
+}
+
+fn write_len<W: Write>(out: &mut W, len: usize) -> Result<(), Box<dyn Error + Send + Sync>> {
+    if len > i32::MAX as usize {
+        return Err(invalid("entry is too large"));
+    }
+    out.write_all(&(len as i32).to_be_bytes())?;
+    Ok(())
+}
+
+fn read_i32(bytes: &mut &[u8]) -> Result<i32, Box<dyn Error + Send + Sync>> {
+    if bytes.len() < 4 {
+        return Err(invalid("unexpected end of data"));
+    }
+    let (head, tail) = bytes.split_at(4);
+    *bytes = tail;
+    Ok(i32::from_be_bytes([head[0], head[1], head[2], head[3]]))
+}
+
+fn read_string(bytes: &mut &[u8], len: i32) -> Result<String, Box<dyn Error + Send + Sync>> {
+    let len = len as usize;
+    if bytes.len() < len {
+        return Err(invalid("unexpected end of data"));
+    }
+    let (head, tail) = bytes.split_at(len);
+    *bytes = tail;
+    String::from_utf8(head.to_vec()).map_err(|_| invalid("entry is not valid UTF-8"))
+}
+
+// Implemented by hand since diesel has no `hstore` support to build on. The
+// binary format is the number of pairs followed by the length prefixed keys
+// and values, a length of -1 marks a `NULL` value.
+impl ToSql<HStore, Pg> for HStoreMap {
+    fn to_sql<W: Write>(&self, out: &mut W) -> Result<IsNull, Box<dyn Error + Send + Sync>> {
+        write_len(out, self.0.len())?;
+        for (key, value) in &self.0 {
+            write_len(out, key.len())?;
+            out.write_all(key.as_bytes())?;
+            match *value {
+                Some(ref value) => {
+                    write_len(out, value.len())?;
+                    out.write_all(value.as_bytes())?;
+                }
+                None => out.write_all(&(-1i32).to_be_bytes())?,
+            }
+        }
+        Ok(IsNull::No)
+    }
+}
+
+impl FromSql<HStore, Pg> for HStoreMap {
+    fn from_sql(bytes: Option<&[u8]>) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        let mut bytes = match bytes {
+            Some(bytes) => bytes,
+            // reuse diesels error for unexpected `NULL` values
+            None => return <Vec<u8> as FromSql<Binary, Pg>>::from_sql(None).map(|_| HStoreMap::default()),
+        };
+        let count = read_i32(&mut bytes)?;
+        if count < 0 {
+            return Err(invalid("negative number of entries"));
+        }
+        // every entry takes at least 8 bytes for the lengths, so a corrupted
+        // count can't reserve more memory than the input justifies
+        let mut map = HashMap::with_capacity((count as usize).min(bytes.len() / 8));
+        for _ in 0..count {
+            let key_len = read_i32(&mut bytes)?;
+            if key_len < 0 {
+                return Err(invalid("key is NULL"));
+            }
+            let key = read_string(&mut bytes, key_len)?;
+            let value = match read_i32(&mut bytes)? {
+                -1 => None,
+                len if len < 0 => return Err(invalid("negative value length")),
+                len => Some(read_string(&mut bytes, len)?),
+            };
+            map.insert(key, value);
+        }
+        if !bytes.is_empty() {
+            return Err(invalid("unexpected trailing data"));
+        }
+        Ok(HStoreMap(map))
+    }
+}
+
+impl FromSqlRow<HStore, Pg> for HStoreMap {
+    fn build_from_row<R: Row<Pg>>(row: &mut R) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        HStoreMap::from_sql(row.take())
+    }
+}
+
+impl Queryable<HStore, Pg> for HStoreMap {
+    type Row = Self;
+
+    fn build(row: Self) -> Self {
+        row
+    }
+}
+
+impl AsExpression<HStore> for HStoreMap {
+    type Expression = Bound<HStore, HStoreMap>;
+
+    fn as_expression(self) -> Self::Expression {
+        Bound::new(self)
+    }
+}
+
+impl<'a> AsExpression<HStore> for &'a HStoreMap {
+    type Expression = Bound<HStore, &'a HStoreMap>;
+
+    fn as_expression(self) -> Self::Expression {
+        Bound::new(self)
+    }
+}
@@ -0,0 +1,240 @@
+use std::cmp::Ordering;
+use std::convert::Infallible;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+
+use diesel::types::BigInt;
+
+use CustomSqlType;
+
+/// Primary key of the table `T`, stored in a `BigInt` column
+///
+/// `T` is the table type generated by `table!`, so ids of different tables
+/// are different types and can't be passed in place of each other. The
+/// table is only used as a marker, an `Id` is as large as an `i64`. Like
+/// any registered type, it can be used with `find`, in filters and in
+/// `Queryable` and `Insertable` structs.
+///
+/// ```
+/// # #[macro_use] extern crate diesel;
+/// # #[macro_use] extern crate diesel_custom_type;
+/// use diesel::prelude::*;
+/// use diesel::pg::Pg;
+/// use diesel::types::{BigInt, ToSql};
+/// use diesel_custom_type::adapters::Id;
+///
+/// table! {
+///     users {
+///         id -> BigInt,
+///         name -> Text,
+///     }
+/// }
+///
+/// table! {
+///     orders {
+///         id -> BigInt,
+///         user_id -> BigInt,
+///     }
+/// }
+///
+/// type UserId = Id<users::table>;
+/// type OrderId = Id<orders::table>;
+///
+/// #[derive(Debug, PartialEq)]
+/// struct Order {
+///     id: OrderId,
+///     user_id: UserId,
+/// }
+///
+/// Queryable! { struct Order { id: OrderId, user_id: UserId, } }
+///
+/// struct NewOrder {
+///     user_id: UserId,
+/// }
+///
+/// Insertable! {
+///     (orders)
+///     struct NewOrder {
+///         user_id: UserId,
+///     }
+/// }
+///
+/// fn orders_of(conn: &diesel::pg::PgConnection, user: UserId) -> QueryResult<Vec<Order>> {
+///     orders::table.filter(orders::user_id.eq(user)).load(conn)
+/// }
+///
+/// # fn main() {
+/// let user = UserId::new(7);
+/// assert_eq!(user.get(), 7);
+/// assert_eq!(user.to_string(), "7");
+/// assert_eq!(format!("{:?}", user), "Id(7)");
+///
+/// assert_eq!(
+///     debug_sql!(users::table.find(user).select(users::name)),
+///     "SELECT `users`.`name` FROM `users` WHERE `users`.`id` = ?"
+/// );
+/// assert_eq!(
+///     debug_sql!(orders::table.filter(orders::user_id.eq(user)).select(orders::id)),
+///     "SELECT `orders`.`id` FROM `orders` WHERE `orders`.`user_id` = ?"
+/// );
+/// let new_order = NewOrder { user_id: user };
+/// assert_eq!(
+///     debug_sql!(diesel::insert(&new_order).into(orders::table)),
+///     "INSERT INTO `orders` (`user_id`) VALUES (?)"
+/// );
+///
+/// let mut bytes = Vec::new();
+/// ToSql::<BigInt, Pg>::to_sql(&user, &mut bytes).unwrap();
+/// assert_eq!(bytes, 7i64.to_be_bytes());
+/// # let _ = orders_of;
+/// # }
+/// ```
+///
+/// Ids of different tables don't mix:
+///
+/// ```compile_fail
+/// # #[macro_use] extern crate diesel;
+/// # extern crate diesel_custom_type;
+/// use diesel_custom_type::adapters::Id;
+///
+/// table! {
+///     users {
+///         id -> BigInt,
+///     }
+/// }
+///
+/// table! {
+///     orders {
+///         id -> BigInt,
+///     }
+/// }
+///
+/// fn cancel_order(_: Id<orders::table>) {}
+///
+/// # fn main() {
+/// cancel_order(Id::<users::table>::new(1));
+/// # }
+/// ```
+///
+/// Round trip through a live database:
+///
+/// ```no_run
+/// # #[macro_use] extern crate diesel;
+/// # extern crate diesel_custom_type;
+/// use diesel::prelude::*;
+/// use diesel::pg::PgConnection;
+/// use diesel_custom_type::adapters::Id;
+/// use diesel_custom_type::testing::verify_custom_type;
+///
+/// table! {
+///     users {
+///         id -> BigInt,
+///         name -> Text,
+///     }
+/// }
+///
+/// # fn main() {
+/// let conn = PgConnection::establish(&::std::env::var("DATABASE_URL").unwrap()).unwrap();
+/// conn.execute("CREATE TEMPORARY TABLE users (id BIGSERIAL PRIMARY KEY, name TEXT NOT NULL)").unwrap();
+/// conn.execute("INSERT INTO users (name) VALUES ('Sean'), ('Tess')").unwrap();
+///
+/// let id = users::table.filter(users::name.eq("Tess")).select(users::id).first::<Id<users::table>>(&conn).unwrap();
+/// assert_eq!(id, Id::new(2));
+/// let name = users::table.find(id).select(users::name).first::<String>(&conn).unwrap();
+/// assert_eq!(name, "Tess");
+///
+/// let ids = [Id::<users::table>::new(1), Id::new(-1), Id::new(i64::MAX)];
+/// let report = verify_custom_type(&conn, &ids).unwrap();
+/// assert!(report.is_ok(), "{:?}", report.mismatches);
+/// # }
+/// ```
+pub struct Id<T> {
+    value: i64,
+    table: PhantomData<fn() -> T>,
+}
+
+impl<T> Id<T> {
+    /// Wraps the raw key `value`
+    pub const fn new(value: i64) -> Self {
+        Id {
+            value,
+            table: PhantomData,
+        }
+    }
+
+    /// Returns the raw key
+    pub const fn get(self) -> i64 {
+        self.value
+    }
+}
+
+// Implemented by hand, derives would require the table marker to implement
+// the traits as well
+impl<T> Clone for Id<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Id<T> {}
+
+impl<T> PartialEq for Id<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+impl<T> Eq for Id<T> {}
+
+impl<T> PartialOrd for Id<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> Ord for Id<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.value.cmp(&other.value)
+    }
+}
+
+impl<T> Hash for Id<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.value.hash(state)
+    }
+}
+
+impl<T> fmt::Debug for Id<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("Id").field(&self.value).finish()
+    }
+}
+
+impl<T> fmt::Display for Id<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&self.value, f)
+    }
+}
+
+impl<T> From<Id<T>> for i64 {
+    fn from(v: Id<T>) -> Self {
+        v.value
+    }
+}
+
+impl<T> CustomSqlType for Id<T> {
+    type DataBaseType = BigInt;
+    type RawType = i64;
+    type Error = Infallible;
+
+    fn to_database_type(&self) -> i64 {
+        self.value
+    }
+
+    fn from_database_type(v: &i64) -> Result<Self, Infallible> {
+        Ok(Id::new(*v))
+    }
+}
+
+register_custom_type!(impl[T] Id<T> where);
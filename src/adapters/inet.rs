@@ -0,0 +1,321 @@
+use std::error::Error;
+use std::fmt;
+use std::io::Write;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+use diesel::expression::AsExpression;
+use diesel::expression::bound::Bound;
+use diesel::pg::{Pg, PgTypeMetadata};
+use diesel::query_builder::QueryId;
+use diesel::row::Row;
+use diesel::types::{Binary, FromSql, FromSqlRow, HasSqlType, IsNull, NotNull, ToSql};
+use diesel::Queryable;
+
+use super::InvalidValueError;
+
+/// SQL type of PostgreSQL `inet` columns
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Inet;
+
+/// SQL type of PostgreSQL `cidr` columns
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Cidr;
+
+macro_rules! pg_network_type {
+    ($SqlType:ident, $oid:expr, $array_oid:expr) => {
+        impl HasSqlType<$SqlType> for Pg {
+            fn metadata() -> PgTypeMetadata {
+                PgTypeMetadata {
+                    oid: $oid,
+                    array_oid: $array_oid,
+                }
+            }
+        }
+
+        impl NotNull for $SqlType {}
+
+        impl QueryId for $SqlType {
+            type QueryId = Self;
+
+            fn has_static_query_id() -> bool {
+                true
+            }
+        }
+
+        impl FromSql<$SqlType, Pg> for PgInet {
+            fn from_sql(bytes: Option<&[u8]>) -> Result<Self, Box<dyn Error + Send + Sync>> {
+                match bytes {
+                    Some(bytes) => Ok(PgInet::decode(bytes)?),
+                    // reuse diesels error for unexpected `NULL` values
+                    None => <Vec<u8> as FromSql<Binary, Pg>>::from_sql(None).map(|_| unreachable!()),
+                }
+            }
+        }
+
+        impl FromSqlRow<$SqlType, Pg> for PgInet {
+            fn build_from_row<R: Row<Pg>>(row: &mut R) -> Result<Self, Box<dyn Error + Send + Sync>> {
+                <PgInet as FromSql<$SqlType, Pg>>::from_sql(row.take())
+            }
+        }
+
+        impl Queryable<$SqlType, Pg> for PgInet {
+            type Row = Self;
+
+            fn build(row: Self) -> Self {
+                row
+            }
+        }
+
+        impl AsExpression<$SqlType> for PgInet {
+            type Expression = Bound<$SqlType, PgInet>;
+
+            fn as_expression(self) -> Self::Expression {
+                Bound::new(self)
+            }
+        }
+
+        impl<'a> AsExpression<$SqlType> for &'a PgInet {
+            type Expression = Bound<$SqlType, &'a PgInet>;
+
+            fn as_expression(self) -> Self::Expression {
+                Bound::new(self)
+            }
+        }
+    };
+}
+
+pg_network_type!(Inet, 869, 1041);
+pg_network_type!(Cidr, 650, 651);
+
+/// Address family values used by PostgreSQL, `PGSQL_AF_INET6` is not the
+/// system `AF_INET6`
+const PGSQL_AF_INET: u8 = 2;
+const PGSQL_AF_INET6: u8 = PGSQL_AF_INET + 1;
+
+/// IP address with a network prefix, as stored in PostgreSQL `inet` and
+/// `cidr` columns
+///
+/// Use this as `RawType` of a custom type with `DataBaseType = Inet` or
+/// `DataBaseType = Cidr`. `inet` columns accept any value, writing a value
+/// with bits set after the prefix to a `cidr` column fails instead of
+/// silently clearing them.
+///
+/// ```
+/// # #[macro_use] extern crate diesel;
+/// # #[macro_use] extern crate diesel_custom_type;
+/// use std::convert::Infallible;
+/// use std::net::IpAddr;
+/// use diesel::prelude::*;
+/// use diesel::pg::Pg;
+/// use diesel::types::ToSql;
+/// use diesel_custom_type::CustomSqlType;
+/// use diesel_custom_type::adapters::{Cidr, PgInet};
+///
+/// table! {
+///     subnets {
+///         id -> Integer,
+///         range -> ::diesel_custom_type::adapters::Cidr,
+///     }
+/// }
+///
+/// #[derive(Debug, Clone, Copy, PartialEq)]
+/// struct Subnet(PgInet);
+///
+/// impl CustomSqlType for Subnet {
+///     type DataBaseType = Cidr;
+///     type RawType = PgInet;
+///     type Error = Infallible;
+///
+///     fn to_database_type(&self) -> PgInet {
+///         self.0
+///     }
+///
+///     fn from_database_type(v: &PgInet) -> Result<Self, Infallible> {
+///         Ok(Subnet(*v))
+///     }
+/// }
+///
+/// register_custom_type!(Subnet);
+///
+/// # fn main() {
+/// let addr: IpAddr = "10.1.0.0".parse().unwrap();
+/// let subnet = Subnet(PgInet::new(addr, 16).unwrap());
+/// let _query = subnets::table.filter(subnets::range.eq(subnet));
+///
+/// let mut bytes = Vec::new();
+/// ToSql::<Cidr, Pg>::to_sql(&subnet.0, &mut bytes).unwrap();
+/// assert_eq!(bytes, [2, 16, 1, 4, 10, 1, 0, 0]);
+///
+/// let host = PgInet::new("10.1.2.3".parse().unwrap(), 16).unwrap();
+/// let err = ToSql::<Cidr, Pg>::to_sql(&host, &mut Vec::new()).unwrap_err();
+/// assert_eq!(err.to_string(), "Invalid value `10.1.2.3/16` for PgInet: bits are set after the network prefix");
+/// # }
+/// ```
+///
+/// Both address families round trip through either SQL type:
+///
+/// ```
+/// # extern crate diesel;
+/// # extern crate diesel_custom_type;
+/// use diesel::pg::Pg;
+/// use diesel::types::{FromSql, ToSql};
+/// use diesel_custom_type::adapters::{Cidr, Inet, PgInet};
+///
+/// # fn main() {
+/// let host = PgInet::from("2001:db8::1".parse::<std::net::IpAddr>().unwrap());
+/// assert_eq!(host.to_string(), "2001:db8::1/128");
+/// let network = PgInet::new("2001:db8::".parse().unwrap(), 32).unwrap();
+///
+/// for v in &[host, network] {
+///     let mut bytes = Vec::new();
+///     ToSql::<Inet, Pg>::to_sql(v, &mut bytes).unwrap();
+///     assert_eq!(<PgInet as FromSql<Inet, Pg>>::from_sql(Some(&bytes)).unwrap(), *v);
+///
+///     let mut bytes = Vec::new();
+///     ToSql::<Cidr, Pg>::to_sql(v, &mut bytes).unwrap();
+///     assert_eq!(<PgInet as FromSql<Cidr, Pg>>::from_sql(Some(&bytes)).unwrap(), *v);
+/// }
+///
+/// let err = PgInet::new("192.0.2.1".parse().unwrap(), 33).unwrap_err();
+/// assert_eq!(err.to_string(), "Invalid value `192.0.2.1/33` for PgInet: prefix is longer than the address");
+/// # }
+/// ```
+///
+/// Round trip through a live database:
+///
+/// ```no_run
+/// # extern crate diesel;
+/// # extern crate diesel_custom_type;
+/// use diesel::{select, Connection, LoadDsl};
+/// use diesel::expression::AsExpression;
+/// use diesel::pg::PgConnection;
+/// use diesel_custom_type::adapters::{Cidr, Inet, PgInet};
+///
+/// # fn main() {
+/// let conn = PgConnection::establish(&::std::env::var("DATABASE_URL").unwrap()).unwrap();
+/// let values = [
+///     PgInet::new("192.0.2.1".parse().unwrap(), 32).unwrap(),
+///     PgInet::new("192.0.2.0".parse().unwrap(), 24).unwrap(),
+///     PgInet::new("2001:db8::1".parse().unwrap(), 128).unwrap(),
+///     PgInet::new("2001:db8::".parse().unwrap(), 32).unwrap(),
+/// ];
+/// for &v in &values {
+///     let read = select(AsExpression::<Inet>::as_expression(v)).get_result::<PgInet>(&conn).unwrap();
+///     assert_eq!(read, v);
+///     let read = select(AsExpression::<Cidr>::as_expression(v)).get_result::<PgInet>(&conn).unwrap();
+///     assert_eq!(read, v);
+/// }
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct PgInet {
+    addr: IpAddr,
+    prefix: u8,
+}
+
+fn max_prefix(addr: &IpAddr) -> u8 {
+    match *addr {
+        IpAddr::V4(_) => 32,
+        IpAddr::V6(_) => 128,
+    }
+}
+
+impl PgInet {
+    /// Creates a value from an address and a prefix length in bits
+    pub fn new(addr: IpAddr, prefix: u8) -> Result<Self, InvalidValueError> {
+        if prefix > max_prefix(&addr) {
+            let value = format!("{}/{}", addr, prefix);
+            return Err(InvalidValueError::new("PgInet", value, "prefix is longer than the address"));
+        }
+        Ok(PgInet { addr, prefix })
+    }
+
+    /// The address
+    pub fn addr(&self) -> IpAddr {
+        self.addr
+    }
+
+    /// The prefix length in bits
+    pub fn prefix(&self) -> u8 {
+        self.prefix
+    }
+
+    /// Returns true if no bits are set after the prefix, as required for
+    /// `cidr` values
+    pub fn is_network(&self) -> bool {
+        let host_bits = u32::from(max_prefix(&self.addr) - self.prefix);
+        match self.addr {
+            IpAddr::V4(addr) => u32::from(addr).checked_shl(32 - host_bits).unwrap_or(0) == 0,
+            IpAddr::V6(addr) => u128::from(addr).checked_shl(128 - host_bits).unwrap_or(0) == 0,
+        }
+    }
+
+    fn encode<W: Write>(&self, is_cidr: bool, out: &mut W) -> Result<IsNull, Box<dyn Error + Send + Sync>> {
+        if is_cidr && !self.is_network() {
+            return Err(Box::new(InvalidValueError::new("PgInet", self, "bits are set after the network prefix")));
+        }
+        match self.addr {
+            IpAddr::V4(addr) => {
+                out.write_all(&[PGSQL_AF_INET, self.prefix, is_cidr as u8, 4])?;
+                out.write_all(&addr.octets())?;
+            }
+            IpAddr::V6(addr) => {
+                out.write_all(&[PGSQL_AF_INET6, self.prefix, is_cidr as u8, 16])?;
+                out.write_all(&addr.octets())?;
+            }
+        }
+        Ok(IsNull::No)
+    }
+
+    fn decode(bytes: &[u8]) -> Result<Self, InvalidValueError> {
+        let invalid = |reason| InvalidValueError::new("PgInet", format!("{:?}", bytes), reason);
+        if bytes.len() < 4 {
+            return Err(invalid("unexpected end of data"));
+        }
+        let (header, addr) = bytes.split_at(4);
+        if usize::from(header[3]) != addr.len() {
+            return Err(invalid("address length does not match the data"));
+        }
+        let addr = match (header[0], addr.len()) {
+            (PGSQL_AF_INET, 4) => IpAddr::V4(Ipv4Addr::new(addr[0], addr[1], addr[2], addr[3])),
+            (PGSQL_AF_INET6, 16) => {
+                let mut octets = [0; 16];
+                octets.copy_from_slice(addr);
+                IpAddr::V6(Ipv6Addr::from(octets))
+            }
+            _ => return Err(invalid("unknown address family")),
+        };
+        PgInet::new(addr, header[1])
+    }
+}
+
+impl From<IpAddr> for PgInet {
+    /// Creates a host address, with a prefix covering the whole address
+    fn from(addr: IpAddr) -> Self {
+        PgInet {
+            addr,
+            prefix: max_prefix(&addr),
+        }
+    }
+}
+
+impl fmt::Display for PgInet {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}/{}", self.addr, self.prefix)
+    }
+}
+
+// Implemented by hand since diesel 0.8 has no network types to build on. The
+// binary format is the address family, the prefix length, a `cidr` flag and
+// the number of address bytes, followed by the address bytes.
+impl ToSql<Inet, Pg> for PgInet {
+    fn to_sql<W: Write>(&self, out: &mut W) -> Result<IsNull, Box<dyn Error + Send + Sync>> {
+        self.encode(false, out)
+    }
+}
+
+impl ToSql<Cidr, Pg> for PgInet {
+    fn to_sql<W: Write>(&self, out: &mut W) -> Result<IsNull, Box<dyn Error + Send + Sync>> {
+        self.encode(true, out)
+    }
+}
@@ -0,0 +1,211 @@
+use std::collections::HashSet;
+use std::convert::Infallible;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+use std::ops::Deref;
+use std::sync::{Arc, OnceLock, RwLock};
+
+use diesel::types::Text;
+
+use CustomSqlType;
+
+/// Bounded cache of shared strings used by `Interned`
+///
+/// Once `capacity` distinct values are cached, further values are still
+/// returned as `Arc<str>` but are no longer added to the cache, so memory
+/// used by the cache itself never grows past the configured capacity.
+pub struct InternCache {
+    capacity: usize,
+    values: OnceLock<RwLock<HashSet<Arc<str>>>>,
+}
+
+impl InternCache {
+    /// Creates an empty cache holding at most `capacity` distinct values
+    pub const fn new(capacity: usize) -> Self {
+        InternCache {
+            capacity,
+            values: OnceLock::new(),
+        }
+    }
+
+    /// Returns the shared copy of `v`, adding it to the cache if there is
+    /// still room
+    pub fn intern(&self, v: &str) -> Arc<str> {
+        let values = self.values.get_or_init(Default::default);
+        if let Some(v) = values.read().unwrap_or_else(|e| e.into_inner()).get(v) {
+            return v.clone();
+        }
+        let mut values = values.write().unwrap_or_else(|e| e.into_inner());
+        if let Some(v) = values.get(v) {
+            return v.clone();
+        }
+        let interned = Arc::<str>::from(v);
+        if values.len() < self.capacity {
+            values.insert(interned.clone());
+        }
+        interned
+    }
+
+    /// Number of values currently cached
+    pub fn len(&self) -> usize {
+        self.values.get().map_or(0, |v| v.read().unwrap_or_else(|e| e.into_inner()).len())
+    }
+
+    /// Returns true if no value is cached yet
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Selects the cache used by an `Interned` column type
+///
+/// Implement this for a marker type to give a column its own cache and
+/// capacity.
+pub trait InternPool: 'static {
+    /// The cache shared by all values using this pool
+    fn cache() -> &'static InternCache;
+}
+
+/// Pool used by `Interned` if no other pool is given, caching up to 1024
+/// distinct values
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SharedPool;
+
+impl InternPool for SharedPool {
+    fn cache() -> &'static InternCache {
+        static CACHE: InternCache = InternCache::new(1024);
+        &CACHE
+    }
+}
+
+/// Text column with few distinct values, deduplicated into shared `Arc<str>`
+/// while reading
+///
+/// Rows holding the same text share one allocation, which keeps memory low
+/// for large result sets of category like columns. Diesel still decodes
+/// every value into a temporary `String` first, only the retained values are
+/// shared.
+///
+/// ```
+/// # extern crate diesel;
+/// # extern crate diesel_custom_type;
+/// use std::sync::Arc;
+/// use diesel::pg::Pg;
+/// use diesel::types::{FromSql, Text};
+/// use diesel_custom_type::adapters::{InternCache, InternPool, Interned};
+///
+/// struct Categories;
+///
+/// impl InternPool for Categories {
+///     fn cache() -> &'static InternCache {
+///         static CACHE: InternCache = InternCache::new(2);
+///         &CACHE
+///     }
+/// }
+///
+/// type Category = Interned<Categories>;
+///
+/// # fn main() {
+/// let load = |v: &str| <Category as FromSql<Text, Pg>>::from_sql(Some(v.as_bytes())).unwrap();
+///
+/// let rows = vec![load("books"), load("games"), load("books")];
+/// assert_eq!(&*rows[0], "books");
+/// assert!(Arc::ptr_eq(rows[0].as_arc(), rows[2].as_arc()));
+///
+/// // The cache is full, further values are not shared anymore
+/// let (a, b) = (load("music"), load("music"));
+/// assert_eq!(a, b);
+/// assert!(!Arc::ptr_eq(a.as_arc(), b.as_arc()));
+/// assert_eq!(Categories::cache().len(), 2);
+/// # }
+/// ```
+pub struct Interned<P: InternPool = SharedPool> {
+    value: Arc<str>,
+    pool: PhantomData<fn() -> P>,
+}
+
+impl<P: InternPool> Interned<P> {
+    /// Interns `v` using the cache of `P`
+    pub fn new(v: &str) -> Self {
+        Interned {
+            value: P::cache().intern(v),
+            pool: PhantomData,
+        }
+    }
+
+    /// Returns the stored text
+    pub fn as_str(&self) -> &str {
+        &self.value
+    }
+
+    /// Returns the shared allocation holding the text
+    pub fn as_arc(&self) -> &Arc<str> {
+        &self.value
+    }
+}
+
+impl<P: InternPool> Clone for Interned<P> {
+    fn clone(&self) -> Self {
+        Interned {
+            value: self.value.clone(),
+            pool: PhantomData,
+        }
+    }
+}
+
+impl<P: InternPool> fmt::Debug for Interned<P> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(&*self.value, f)
+    }
+}
+
+impl<P: InternPool> fmt::Display for Interned<P> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.value)
+    }
+}
+
+impl<P: InternPool> PartialEq for Interned<P> {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+impl<P: InternPool> Eq for Interned<P> {}
+
+impl<P: InternPool> Hash for Interned<P> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.value.hash(state)
+    }
+}
+
+impl<P: InternPool> Deref for Interned<P> {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.value
+    }
+}
+
+impl<'a, P: InternPool> From<&'a str> for Interned<P> {
+    fn from(v: &'a str) -> Self {
+        Interned::new(v)
+    }
+}
+
+impl<P: InternPool> CustomSqlType for Interned<P> {
+    type DataBaseType = Text;
+    type RawType = String;
+    type Error = Infallible;
+
+    fn to_database_type(&self) -> String {
+        self.value.to_string()
+    }
+
+    fn from_database_type(v: &String) -> Result<Self, Infallible> {
+        Ok(Interned::new(v))
+    }
+}
+
+register_custom_type!(impl[P] Interned<P> where P: InternPool);
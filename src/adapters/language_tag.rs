@@ -0,0 +1,183 @@
+use std::fmt;
+use std::str::FromStr;
+
+use diesel::types::Text;
+
+use CustomSqlType;
+use super::InvalidValueError;
+
+/// BCP 47 language tag like `en-US` or `zh-Hant-TW` stored in a `Text` column
+///
+/// The tag is validated structurally (language, optional script and region,
+/// followed by variant, extension and private use subtags) and canonicalized
+/// on construction: the language is lowercase, the script titlecase and the
+/// region uppercase. The registry of assigned subtags is not consulted.
+///
+/// Only available with the `language-tag` feature.
+///
+/// ```
+/// # extern crate diesel;
+/// # extern crate diesel_custom_type;
+/// use diesel_custom_type::CustomSqlType;
+/// use diesel_custom_type::adapters::LanguageTag;
+///
+/// # fn main() {
+/// let tag = LanguageTag::from_database_type(&"pt-br".to_string()).unwrap();
+/// assert_eq!(tag.to_database_type(), "pt-BR");
+/// assert_eq!(tag.language(), "pt");
+/// assert_eq!(tag.region(), Some("BR"));
+/// assert_eq!(LanguageTag::from_database_type(&tag.to_database_type()).unwrap(), tag);
+///
+/// let tag = "ZH-hant-tw".parse::<LanguageTag>().unwrap();
+/// assert_eq!(tag.as_str(), "zh-Hant-TW");
+/// assert_eq!(tag.script(), Some("Hant"));
+/// assert_eq!("de".parse::<LanguageTag>().unwrap().region(), None);
+///
+/// let err = LanguageTag::from_database_type(&"en_US".to_string()).unwrap_err();
+/// assert_eq!(err.to_string(), "Invalid value `en_US` for LanguageTag: malformed language tag");
+/// assert!("en-".parse::<LanguageTag>().is_err());
+/// assert!("en-u".parse::<LanguageTag>().is_err());
+/// assert_eq!("EN-u-CA-gregory-X-A".parse::<LanguageTag>().unwrap().as_str(), "en-u-ca-gregory-x-a");
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct LanguageTag {
+    tag: String,
+    has_script: bool,
+    has_region: bool,
+}
+
+fn is_alpha(s: &str) -> bool {
+    s.bytes().all(|b| b.is_ascii_alphabetic())
+}
+
+fn is_digit(s: &str) -> bool {
+    s.bytes().all(|b| b.is_ascii_digit())
+}
+
+fn is_alnum(s: &str) -> bool {
+    s.bytes().all(|b| b.is_ascii_alphanumeric())
+}
+
+fn titlecase(s: &str) -> String {
+    let mut s = s.to_ascii_lowercase();
+    s[..1].make_ascii_uppercase();
+    s
+}
+
+fn parse(s: &str) -> Option<LanguageTag> {
+    let mut subtags = s.split('-').peekable();
+    let language = subtags.next()?;
+    if !is_alpha(language) || !(language.len() >= 2 && language.len() <= 3 || language.len() >= 5 && language.len() <= 8) {
+        return None;
+    }
+    let mut canonical = vec![language.to_ascii_lowercase()];
+    let mut has_script = false;
+    let mut has_region = false;
+    if let Some(&script) = subtags.peek() {
+        if script.len() == 4 && is_alpha(script) {
+            canonical.push(titlecase(script));
+            has_script = true;
+            subtags.next();
+        }
+    }
+    if let Some(&region) = subtags.peek() {
+        if region.len() == 2 && is_alpha(region) || region.len() == 3 && is_digit(region) {
+            canonical.push(region.to_ascii_uppercase());
+            has_region = true;
+            subtags.next();
+        }
+    }
+    // variants, followed by extension and private use sections each
+    // introduced by a single character subtag
+    let mut singleton = None;
+    let mut section_empty = false;
+    for subtag in subtags {
+        if subtag.is_empty() || subtag.len() > 8 || !is_alnum(subtag) {
+            return None;
+        }
+        // everything after `x` is private use, which allows single characters
+        if subtag.len() == 1 && singleton != Some('x') {
+            if section_empty {
+                return None;
+            }
+            singleton = Some(subtag.as_bytes()[0].to_ascii_lowercase() as char);
+            section_empty = true;
+        } else {
+            let variant = subtag.len() >= 5 || subtag.len() == 4 && subtag.as_bytes()[0].is_ascii_digit();
+            if singleton.is_none() && !variant {
+                return None;
+            }
+            section_empty = false;
+        }
+        canonical.push(subtag.to_ascii_lowercase());
+    }
+    if section_empty {
+        return None;
+    }
+    Some(LanguageTag {
+        tag: canonical.join("-"),
+        has_script,
+        has_region,
+    })
+}
+
+impl LanguageTag {
+    /// Returns the canonical tag
+    pub fn as_str(&self) -> &str {
+        &self.tag
+    }
+
+    /// Returns the primary language subtag, e.g. `en`
+    pub fn language(&self) -> &str {
+        self.tag.split('-').next().unwrap_or("")
+    }
+
+    /// Returns the script subtag, e.g. `Hant`
+    pub fn script(&self) -> Option<&str> {
+        if self.has_script {
+            self.tag.split('-').nth(1)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the region subtag, e.g. `US` or `419`
+    pub fn region(&self) -> Option<&str> {
+        if self.has_region {
+            self.tag.split('-').nth(if self.has_script { 2 } else { 1 })
+        } else {
+            None
+        }
+    }
+}
+
+impl fmt::Display for LanguageTag {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.tag)
+    }
+}
+
+impl FromStr for LanguageTag {
+    type Err = InvalidValueError;
+
+    fn from_str(s: &str) -> Result<Self, InvalidValueError> {
+        parse(s).ok_or_else(|| InvalidValueError::new("LanguageTag", s, "malformed language tag"))
+    }
+}
+
+impl CustomSqlType for LanguageTag {
+    type DataBaseType = Text;
+    type RawType = String;
+    type Error = InvalidValueError;
+
+    fn to_database_type(&self) -> String {
+        self.tag.clone()
+    }
+
+    fn from_database_type(v: &String) -> Result<Self, InvalidValueError> {
+        v.parse()
+    }
+}
+
+register_custom_type!(LanguageTag);
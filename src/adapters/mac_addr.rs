@@ -0,0 +1,125 @@
+use std::fmt;
+use std::str::FromStr;
+
+use diesel::types::Text;
+
+use CustomSqlType;
+use super::InvalidValueError;
+
+/// MAC address stored in a `Text` column
+///
+/// Always written in the canonical lowercase `aa:bb:cc:dd:ee:ff` form. Reading
+/// accepts colon or dash separated pairs as well as 12 bare hex digits, in
+/// any case.
+///
+/// ```
+/// # extern crate diesel;
+/// # extern crate diesel_custom_type;
+/// use diesel_custom_type::CustomSqlType;
+/// use diesel_custom_type::adapters::MacAddr;
+///
+/// # fn main() {
+/// let expected = MacAddr([0xaa, 0xbb, 0xcc, 0x0d, 0xee, 0xff]);
+/// for input in &["aa:bb:cc:0d:ee:ff", "AA-BB-CC-0D-EE-FF", "AaBbCc0dEeFf"] {
+///     let mac = MacAddr::from_database_type(&input.to_string()).unwrap();
+///     assert_eq!(mac, expected);
+///     assert_eq!(mac.to_database_type(), "aa:bb:cc:0d:ee:ff");
+/// }
+///
+/// let err = MacAddr::from_database_type(&"aa:bb-cc:dd:ee:ff".to_string()).unwrap_err();
+/// assert_eq!(err.to_string(), "Invalid value `aa:bb-cc:dd:ee:ff` for MacAddr: expected six hex encoded bytes");
+/// # }
+/// ```
+///
+/// Round trip through a live database:
+///
+/// ```no_run
+/// # extern crate diesel;
+/// # extern crate diesel_custom_type;
+/// use diesel::Connection;
+/// use diesel::pg::PgConnection;
+/// use diesel_custom_type::adapters::MacAddr;
+/// use diesel_custom_type::testing::verify_custom_type;
+///
+/// # fn main() {
+/// let conn = PgConnection::establish(&::std::env::var("DATABASE_URL").unwrap()).unwrap();
+/// let report = verify_custom_type(&conn, &[MacAddr([0; 6]), MacAddr([0xaa, 0xbb, 0xcc, 0x0d, 0xee, 0xff])]).unwrap();
+/// assert!(report.is_ok(), "{:?}", report.mismatches);
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct MacAddr(pub [u8; 6]);
+
+impl MacAddr {
+    /// Returns the bytes of the address
+    pub fn octets(&self) -> [u8; 6] {
+        self.0
+    }
+}
+
+fn hex_pair(s: &str) -> Option<u8> {
+    if s.len() == 2 && s.bytes().all(|b| b.is_ascii_hexdigit()) {
+        u8::from_str_radix(s, 16).ok()
+    } else {
+        None
+    }
+}
+
+fn parse(s: &str) -> Option<[u8; 6]> {
+    let pairs = if s.contains(':') {
+        s.split(':').collect::<Vec<_>>()
+    } else if s.contains('-') {
+        s.split('-').collect::<Vec<_>>()
+    } else if s.len() == 12 && s.is_ascii() {
+        (0..6).map(|i| &s[2 * i..2 * i + 2]).collect::<Vec<_>>()
+    } else {
+        return None;
+    };
+    if pairs.len() != 6 {
+        return None;
+    }
+    let mut bytes = [0; 6];
+    for (byte, pair) in bytes.iter_mut().zip(pairs) {
+        *byte = hex_pair(pair)?;
+    }
+    Some(bytes)
+}
+
+impl fmt::Display for MacAddr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let b = self.0;
+        write!(f, "{:02x}:{:02x}:{:02x}:{:02x}:{:02x}:{:02x}", b[0], b[1], b[2], b[3], b[4], b[5])
+    }
+}
+
+impl FromStr for MacAddr {
+    type Err = InvalidValueError;
+
+    fn from_str(s: &str) -> Result<Self, InvalidValueError> {
+        parse(s)
+            .map(MacAddr)
+            .ok_or_else(|| InvalidValueError::new("MacAddr", s, "expected six hex encoded bytes"))
+    }
+}
+
+impl From<[u8; 6]> for MacAddr {
+    fn from(v: [u8; 6]) -> Self {
+        MacAddr(v)
+    }
+}
+
+impl CustomSqlType for MacAddr {
+    type DataBaseType = Text;
+    type RawType = String;
+    type Error = InvalidValueError;
+
+    fn to_database_type(&self) -> String {
+        self.to_string()
+    }
+
+    fn from_database_type(v: &String) -> Result<Self, InvalidValueError> {
+        v.parse()
+    }
+}
+
+register_custom_type!(MacAddr);
@@ -0,0 +1,189 @@
+use std::cmp::Ordering;
+use std::convert::Infallible;
+use std::fmt;
+use std::marker::PhantomData;
+use std::ops::{Add, Sub};
+
+use diesel::types::{BigInt, Double, Float, Integer, SmallInt};
+
+use CustomSqlType;
+
+/// Rust types usable as representation of a [`Measure`](struct.Measure.html)
+pub trait MeasureRepr: Copy {
+    /// [Diesel type](http://docs.diesel.rs/diesel/types/index.html) used to store the value
+    type DataBaseType;
+}
+
+macro_rules! measure_repr {
+    ($($Repr:ty => $SqlType:ty,)+) => {$(
+        impl MeasureRepr for $Repr {
+            type DataBaseType = $SqlType;
+        }
+    )+}
+}
+
+measure_repr! {
+    i16 => SmallInt,
+    i32 => Integer,
+    i64 => BigInt,
+    f32 => Float,
+    f64 => Double,
+}
+
+/// Quantity tagged with a unit marker type
+///
+/// `Unit` is only used at compile time, so `Measure<Meters>` and
+/// `Measure<Kilograms>` can not be mixed up while sharing the same diesel
+/// implementations. The SQL type is selected by `Repr`, `Double` for the
+/// default `f64`.
+///
+/// ```
+/// # #[macro_use] extern crate diesel;
+/// # extern crate diesel_custom_type;
+/// use diesel::pg::Pg;
+/// use diesel::types::{Double, ToSql};
+/// use diesel_custom_type::CustomSqlType;
+/// use diesel_custom_type::adapters::Measure;
+///
+/// #[derive(Debug)]
+/// enum Meters {}
+/// #[derive(Debug)]
+/// enum Kilograms {}
+///
+/// table! {
+///     parcels {
+///         id -> Integer,
+///         length -> Double,
+///         weight -> Double,
+///     }
+/// }
+///
+/// struct Parcel {
+///     id: i32,
+///     length: Measure<Meters>,
+///     weight: Measure<Kilograms>,
+/// }
+///
+/// Queryable! {
+///     struct Parcel {
+///         id: i32,
+///         length: Measure<Meters>,
+///         weight: Measure<Kilograms>,
+///     }
+/// }
+///
+/// struct NewParcel {
+///     length: Measure<Meters>,
+///     weight: Measure<Kilograms>,
+/// }
+///
+/// Insertable! {
+///     (parcels)
+///     struct NewParcel {
+///         length: Measure<Meters>,
+///         weight: Measure<Kilograms>,
+///     }
+/// }
+///
+/// # fn main() {
+/// let length = Measure::<Meters>::new(1.5) + Measure::new(0.5);
+/// assert_eq!(length.value(), 2.0);
+/// assert_eq!(<Measure<Kilograms>>::from_database_type(&3.0).unwrap().value(), 3.0);
+///
+/// let mut bytes = Vec::new();
+/// ToSql::<Double, Pg>::to_sql(&length, &mut bytes).unwrap();
+/// assert_eq!(bytes, 2.0f64.to_bits().to_be_bytes());
+/// # }
+/// ```
+///
+/// Mixing units does not compile:
+///
+/// ```compile_fail
+/// # extern crate diesel_custom_type;
+/// # use diesel_custom_type::adapters::Measure;
+/// enum Meters {}
+/// enum Kilograms {}
+///
+/// # fn main() {
+/// let sum = Measure::<Meters>::new(1.0) + Measure::<Kilograms>::new(1.0);
+/// # }
+/// ```
+pub struct Measure<Unit, Repr = f64> {
+    value: Repr,
+    unit: PhantomData<fn() -> Unit>,
+}
+
+impl<Unit, Repr> Measure<Unit, Repr> {
+    /// Creates a new quantity
+    pub fn new(value: Repr) -> Self {
+        Measure {
+            value,
+            unit: PhantomData,
+        }
+    }
+}
+
+impl<Unit, Repr: Copy> Measure<Unit, Repr> {
+    /// Returns the value without unit
+    pub fn value(&self) -> Repr {
+        self.value
+    }
+}
+
+impl<Unit, Repr: Clone> Clone for Measure<Unit, Repr> {
+    fn clone(&self) -> Self {
+        Measure::new(self.value.clone())
+    }
+}
+
+impl<Unit, Repr: Copy> Copy for Measure<Unit, Repr> {}
+
+impl<Unit, Repr: fmt::Debug> fmt::Debug for Measure<Unit, Repr> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.value.fmt(f)
+    }
+}
+
+impl<Unit, Repr: PartialEq> PartialEq for Measure<Unit, Repr> {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+impl<Unit, Repr: PartialOrd> PartialOrd for Measure<Unit, Repr> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.value.partial_cmp(&other.value)
+    }
+}
+
+impl<Unit, Repr: Add<Output = Repr>> Add for Measure<Unit, Repr> {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Measure::new(self.value + other.value)
+    }
+}
+
+impl<Unit, Repr: Sub<Output = Repr>> Sub for Measure<Unit, Repr> {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        Measure::new(self.value - other.value)
+    }
+}
+
+impl<Unit, Repr: MeasureRepr> CustomSqlType for Measure<Unit, Repr> {
+    type DataBaseType = Repr::DataBaseType;
+    type RawType = Repr;
+    type Error = Infallible;
+
+    fn to_database_type(&self) -> Repr {
+        self.value
+    }
+
+    fn from_database_type(v: &Repr) -> Result<Self, Infallible> {
+        Ok(Measure::new(*v))
+    }
+}
+
+register_custom_type!(impl[Unit, Repr] Measure<Unit, Repr> where Repr: MeasureRepr);
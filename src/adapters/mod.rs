@@ -0,0 +1,82 @@
+//! Ready to use custom types and wrappers for common column encodings
+//!
+//! Every type in this module already implements `CustomSqlType` and is
+//! registered with diesel, so it can be used in `Queryable` and `Insertable`
+//! structs directly.
+
+#[cfg(feature = "array-set")]
+mod array_set;
+mod base64;
+#[cfg(feature = "base64")]
+mod base64_bytes;
+#[cfg(feature = "country-code")]
+mod country_code;
+#[cfg(feature = "currency-code")]
+mod currency_code;
+#[cfg(feature = "mime")]
+mod db_mime;
+mod db_path;
+mod digest;
+mod dual_read;
+mod dynamic_pg_enum;
+mod email;
+mod empty_as_none;
+mod encrypted;
+mod enum_set;
+mod error;
+mod hstore;
+mod id;
+mod inet;
+mod interned;
+#[cfg(feature = "language-tag")]
+mod language_tag;
+mod mac_addr;
+mod measure;
+mod money;
+mod non_zero;
+#[cfg(feature = "not-nan")]
+mod not_nan;
+mod nullable_array;
+mod phone;
+#[cfg(feature = "wkt-point")]
+mod point;
+mod redacted;
+mod sentinel_none;
+
+#[cfg(feature = "array-set")]
+pub use self::array_set::{ArraySet, LenientArraySet, SetCollection};
+#[cfg(feature = "base64")]
+pub use self::base64_bytes::Base64Bytes;
+#[cfg(feature = "country-code")]
+pub use self::country_code::{CountryCode, LenientCountryCode};
+#[cfg(feature = "currency-code")]
+pub use self::currency_code::CurrencyCode;
+#[cfg(feature = "mime")]
+pub use self::db_mime::{DbMime, Mime};
+pub use self::db_path::DbPath;
+pub use self::digest::{Digest32, HexDigest32};
+pub use self::dual_read::{DualRead, LegacyEncoding};
+pub use self::dynamic_pg_enum::{register_pg_enum_labels, set_unregistered_label_hook, DynamicPgEnum, PgEnum};
+pub use self::email::Email;
+pub use self::empty_as_none::{BlankAsNone, EmptyAsNone};
+pub use self::encrypted::{CipherProvider, CiphertextStorage, Encrypted, Plaintext};
+pub use self::enum_set::{EnumSet, EnumSetIter, LenientEnumSet};
+pub use self::error::InvalidValueError;
+pub use self::hstore::{HStore, HStoreMap};
+pub use self::id::Id;
+pub use self::inet::{Cidr, Inet, PgInet};
+pub use self::interned::{InternCache, InternPool, Interned, SharedPool};
+#[cfg(feature = "language-tag")]
+pub use self::language_tag::LanguageTag;
+pub use self::mac_addr::MacAddr;
+pub use self::measure::{Measure, MeasureRepr};
+pub use self::money::{Money, PgMoney};
+pub use self::non_zero::{NonZeroBigInt, NonZeroInt};
+#[cfg(feature = "not-nan")]
+pub use self::not_nan::{Finite, NotNan};
+pub use self::nullable_array::{NullableArray, StrictArray};
+pub use self::phone::{LenientPhoneE164, PhoneE164};
+#[cfg(feature = "wkt-point")]
+pub use self::point::{LatLng, Point, WktPoint};
+pub use self::redacted::{Redacted, RedactedValueError};
+pub use self::sentinel_none::{SentinelNone, SentinelRaw};
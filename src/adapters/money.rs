@@ -0,0 +1,193 @@
+use std::error::Error;
+use std::io::Write;
+
+use diesel::expression::AsExpression;
+use diesel::expression::bound::Bound;
+use diesel::pg::{Pg, PgTypeMetadata};
+use diesel::query_builder::QueryId;
+use diesel::row::Row;
+use diesel::types::{BigInt, FromSql, FromSqlRow, HasSqlType, IsNull, NotNull, ToSql};
+use diesel::Queryable;
+
+/// SQL type of PostgreSQL `money` columns
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Money;
+
+impl HasSqlType<Money> for Pg {
+    fn metadata() -> PgTypeMetadata {
+        PgTypeMetadata {
+            oid: 790,
+            array_oid: 791,
+        }
+    }
+}
+
+impl NotNull for Money {}
+
+impl QueryId for Money {
+    type QueryId = Self;
+
+    fn has_static_query_id() -> bool {
+        true
+    }
+}
+
+/// Raw value of a PostgreSQL `money` column
+///
+/// The binary protocol sends `money` as a signed 64 bit count of the
+/// smallest currency unit, independent of the locale used for the text
+/// format. The number of fractional digits is still defined by the
+/// `lc_monetary` setting of the server, for most locales a value of `1`
+/// means one cent.
+///
+/// Use this as `RawType` of a custom type with `DataBaseType = Money`:
+///
+/// ```
+/// # #[macro_use] extern crate diesel_custom_type;
+/// # extern crate diesel;
+/// use diesel::pg::Pg;
+/// use diesel::types::{FromSql, ToSql};
+/// use diesel_custom_type::CustomSqlType;
+/// use diesel_custom_type::adapters::{Money, PgMoney};
+///
+/// #[derive(Debug, Clone, Copy, PartialEq)]
+/// struct Price {
+///     cents: i64,
+/// }
+///
+/// impl CustomSqlType for Price {
+///     type DataBaseType = Money;
+///     type RawType = PgMoney;
+///     type Error = ::std::convert::Infallible;
+///
+///     fn to_database_type(&self) -> PgMoney {
+///         PgMoney(self.cents)
+///     }
+///
+///     fn from_database_type(v: &PgMoney) -> Result<Self, Self::Error> {
+///         Ok(Price { cents: v.cents() })
+///     }
+/// }
+///
+/// register_custom_type!(Price);
+///
+/// # fn main() {
+/// let refund = Price { cents: -1250 };
+/// let mut bytes = Vec::new();
+/// ToSql::<Money, Pg>::to_sql(&refund.to_database_type(), &mut bytes).unwrap();
+/// assert_eq!(bytes, (-1250i64).to_be_bytes());
+///
+/// let read = <Price as FromSql<Money, Pg>>::from_sql(Some(&bytes)).unwrap();
+/// assert_eq!(read, refund);
+/// # }
+/// ```
+///
+/// Round trip through a live database:
+///
+/// ```no_run
+/// # #[macro_use] extern crate diesel_custom_type;
+/// # extern crate diesel;
+/// use diesel::{select, Connection, LoadDsl};
+/// use diesel::expression::AsExpression;
+/// use diesel::pg::PgConnection;
+/// use diesel_custom_type::adapters::{Money, PgMoney};
+/// use diesel_custom_type::testing::verify_custom_type;
+/// # use diesel_custom_type::CustomSqlType;
+/// #
+/// # #[derive(Debug, Clone, Copy, PartialEq)]
+/// # struct Price {
+/// #     cents: i64,
+/// # }
+/// #
+/// # impl CustomSqlType for Price {
+/// #     type DataBaseType = Money;
+/// #     type RawType = PgMoney;
+/// #     type Error = ::std::convert::Infallible;
+/// #
+/// #     fn to_database_type(&self) -> PgMoney {
+/// #         PgMoney(self.cents)
+/// #     }
+/// #
+/// #     fn from_database_type(v: &PgMoney) -> Result<Self, Self::Error> {
+/// #         Ok(Price { cents: v.cents() })
+/// #     }
+/// # }
+/// #
+/// # register_custom_type!(Price);
+///
+/// # fn main() {
+/// let conn = PgConnection::establish(&::std::env::var("DATABASE_URL").unwrap()).unwrap();
+/// for &v in &[PgMoney(0), PgMoney(1999), PgMoney(-1999), PgMoney(i64::MIN), PgMoney(i64::MAX)] {
+///     let read = select(AsExpression::<Money>::as_expression(v)).get_result::<PgMoney>(&conn).unwrap();
+///     assert_eq!(read, v);
+/// }
+///
+/// let prices = [Price { cents: 0 }, Price { cents: -1250 }, Price { cents: i64::MAX }];
+/// let report = verify_custom_type(&conn, &prices).unwrap();
+/// assert!(report.is_ok(), "{:?}", report.mismatches);
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct PgMoney(pub i64);
+
+impl PgMoney {
+    /// The amount in the smallest currency unit
+    pub fn cents(&self) -> i64 {
+        self.0
+    }
+}
+
+impl From<i64> for PgMoney {
+    fn from(cents: i64) -> Self {
+        PgMoney(cents)
+    }
+}
+
+impl From<PgMoney> for i64 {
+    fn from(v: PgMoney) -> Self {
+        v.0
+    }
+}
+
+// `money` shares the wire format of `int8`
+impl ToSql<Money, Pg> for PgMoney {
+    fn to_sql<W: Write>(&self, out: &mut W) -> Result<IsNull, Box<dyn Error + Send + Sync>> {
+        ToSql::<BigInt, Pg>::to_sql(&self.0, out)
+    }
+}
+
+impl FromSql<Money, Pg> for PgMoney {
+    fn from_sql(bytes: Option<&[u8]>) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        <i64 as FromSql<BigInt, Pg>>::from_sql(bytes).map(PgMoney)
+    }
+}
+
+impl FromSqlRow<Money, Pg> for PgMoney {
+    fn build_from_row<R: Row<Pg>>(row: &mut R) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        PgMoney::from_sql(row.take())
+    }
+}
+
+impl Queryable<Money, Pg> for PgMoney {
+    type Row = Self;
+
+    fn build(row: Self) -> Self {
+        row
+    }
+}
+
+impl AsExpression<Money> for PgMoney {
+    type Expression = Bound<Money, PgMoney>;
+
+    fn as_expression(self) -> Self::Expression {
+        Bound::new(self)
+    }
+}
+
+impl<'a> AsExpression<Money> for &'a PgMoney {
+    type Expression = Bound<Money, &'a PgMoney>;
+
+    fn as_expression(self) -> Self::Expression {
+        Bound::new(self)
+    }
+}
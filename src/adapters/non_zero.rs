@@ -0,0 +1,107 @@
+use std::num::{NonZeroI32, NonZeroI64};
+use std::ops::Deref;
+
+use diesel::types::{BigInt, Integer};
+
+use CustomSqlType;
+use super::InvalidValueError;
+
+macro_rules! non_zero_adapter {
+    ($(#[$meta:meta])* $Name:ident($NonZero:ty, $Raw:ty) => $SqlType:ty) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+        pub struct $Name(pub $NonZero);
+
+        impl $Name {
+            /// Wraps `v`, returns `None` if it is zero
+            pub fn new(v: $Raw) -> Option<Self> {
+                <$NonZero>::new(v).map($Name)
+            }
+
+            /// Returns the contained value as primitive type
+            pub fn get(self) -> $Raw {
+                self.0.get()
+            }
+        }
+
+        impl Deref for $Name {
+            type Target = $NonZero;
+
+            fn deref(&self) -> &$NonZero {
+                &self.0
+            }
+        }
+
+        impl From<$NonZero> for $Name {
+            fn from(v: $NonZero) -> Self {
+                $Name(v)
+            }
+        }
+
+        impl From<$Name> for $NonZero {
+            fn from(v: $Name) -> Self {
+                v.0
+            }
+        }
+
+        impl CustomSqlType for $Name {
+            type DataBaseType = $SqlType;
+            type RawType = $Raw;
+            type Error = InvalidValueError;
+
+            fn to_database_type(&self) -> $Raw {
+                self.get()
+            }
+
+            fn from_database_type(v: &$Raw) -> Result<Self, InvalidValueError> {
+                $Name::new(*v).ok_or_else(|| InvalidValueError::new(stringify!($Name), v, "zero is not allowed"))
+            }
+        }
+
+        register_custom_type!($Name);
+    }
+}
+
+non_zero_adapter! {
+    /// `NonZeroI32` stored in an `Integer` column
+    ///
+    /// Reading zero results in an error. `Option<NonZeroInt>` can be used for
+    /// nullable columns and has the same size as `i32`.
+    ///
+    /// ```
+    /// # extern crate diesel;
+    /// # extern crate diesel_custom_type;
+    /// use std::mem::size_of;
+    /// use diesel::pg::Pg;
+    /// use diesel::types::{FromSql, Integer, Nullable, ToSql};
+    /// use diesel_custom_type::CustomSqlType;
+    /// use diesel_custom_type::adapters::NonZeroInt;
+    ///
+    /// # fn main() {
+    /// let id = NonZeroInt::new(42).unwrap();
+    /// assert_eq!(NonZeroInt::from_database_type(&id.to_database_type()).unwrap(), id);
+    ///
+    /// let err = NonZeroInt::from_database_type(&0).unwrap_err();
+    /// assert_eq!(err.to_string(), "Invalid value `0` for NonZeroInt: zero is not allowed");
+    ///
+    /// assert_eq!(size_of::<Option<NonZeroInt>>(), size_of::<i32>());
+    /// let null = <Option<NonZeroInt> as FromSql<Nullable<Integer>, Pg>>::from_sql(None).unwrap();
+    /// assert_eq!(null, None);
+    /// let value = <Option<NonZeroInt> as FromSql<Nullable<Integer>, Pg>>::from_sql(Some(&[0, 0, 0, 42])).unwrap();
+    /// assert_eq!(value, Some(id));
+    ///
+    /// let mut bytes = Vec::new();
+    /// ToSql::<Nullable<Integer>, Pg>::to_sql(&Some(id), &mut bytes).unwrap();
+    /// assert_eq!(bytes, [0, 0, 0, 42]);
+    /// # }
+    /// ```
+    NonZeroInt(NonZeroI32, i32) => Integer
+}
+
+non_zero_adapter! {
+    /// `NonZeroI64` stored in a `BigInt` column
+    ///
+    /// Reading zero results in an error. `Option<NonZeroBigInt>` can be used
+    /// for nullable columns and has the same size as `i64`.
+    NonZeroBigInt(NonZeroI64, i64) => BigInt
+}
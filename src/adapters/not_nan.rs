@@ -0,0 +1,104 @@
+use std::cmp::Ordering;
+use std::ops::Deref;
+
+use diesel::types::Double;
+
+use CustomSqlType;
+use super::InvalidValueError;
+
+/// `f64` stored in a `Double` column that is guaranteed to never be NaN
+///
+/// NaN can not be written, because a `NotNan` can only be constructed from
+/// valid values. Reading NaN results in an error. Set `ALLOW_INFINITE` to
+/// `false` (or use [`Finite`](type.Finite.html)) to reject infinite values
+/// as well.
+///
+/// Only available with the `not-nan` feature.
+///
+/// ```
+/// # extern crate diesel;
+/// # extern crate diesel_custom_type;
+/// use std::f64;
+/// use diesel_custom_type::CustomSqlType;
+/// use diesel_custom_type::adapters::{Finite, NotNan};
+///
+/// # fn main() {
+/// let score: NotNan = NotNan::new(4.5).unwrap();
+/// assert_eq!(score.to_database_type(), 4.5);
+/// assert_eq!(<NotNan>::from_database_type(&4.5).unwrap(), score);
+///
+/// assert!(<NotNan>::new(f64::NAN).is_none());
+/// let err = <NotNan>::from_database_type(&f64::NAN).unwrap_err();
+/// assert_eq!(err.to_string(), "Invalid value `NaN` for NotNan: NaN is not allowed");
+///
+/// assert!(<NotNan>::from_database_type(&f64::INFINITY).is_ok());
+/// assert!(Finite::new(f64::NEG_INFINITY).is_none());
+/// let err = Finite::from_database_type(&f64::INFINITY).unwrap_err();
+/// assert_eq!(err.to_string(), "Invalid value `inf` for NotNan: infinite values are not allowed");
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NotNan<const ALLOW_INFINITE: bool = true>(f64);
+
+/// `NotNan` additionally rejecting infinite values
+pub type Finite = NotNan<false>;
+
+impl<const ALLOW_INFINITE: bool> NotNan<ALLOW_INFINITE> {
+    /// Wraps `v`, returns `None` if the value is not allowed
+    pub fn new(v: f64) -> Option<Self> {
+        Self::check(v).ok()
+    }
+
+    /// Unwraps the contained value
+    pub fn into_inner(self) -> f64 {
+        self.0
+    }
+
+    fn check(v: f64) -> Result<Self, InvalidValueError> {
+        if v.is_nan() {
+            Err(InvalidValueError::new("NotNan", v, "NaN is not allowed"))
+        } else if !ALLOW_INFINITE && v.is_infinite() {
+            Err(InvalidValueError::new("NotNan", v, "infinite values are not allowed"))
+        } else {
+            Ok(NotNan(v))
+        }
+    }
+}
+
+impl<const ALLOW_INFINITE: bool> Deref for NotNan<ALLOW_INFINITE> {
+    type Target = f64;
+
+    fn deref(&self) -> &f64 {
+        &self.0
+    }
+}
+
+impl<const ALLOW_INFINITE: bool> Eq for NotNan<ALLOW_INFINITE> {}
+
+impl<const ALLOW_INFINITE: bool> PartialOrd for NotNan<ALLOW_INFINITE> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<const ALLOW_INFINITE: bool> Ord for NotNan<ALLOW_INFINITE> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.partial_cmp(&other.0).expect("NotNan never contains NaN")
+    }
+}
+
+impl<const ALLOW_INFINITE: bool> CustomSqlType for NotNan<ALLOW_INFINITE> {
+    type DataBaseType = Double;
+    type RawType = f64;
+    type Error = InvalidValueError;
+
+    fn to_database_type(&self) -> f64 {
+        self.0
+    }
+
+    fn from_database_type(v: &f64) -> Result<Self, InvalidValueError> {
+        Self::check(*v)
+    }
+}
+
+register_custom_type!(impl[const ALLOW_INFINITE: bool] NotNan<ALLOW_INFINITE> where);
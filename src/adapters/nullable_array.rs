@@ -0,0 +1,397 @@
+use std::error::Error;
+use std::io::Write;
+use std::ops::{Deref, DerefMut};
+
+use diesel::expression::AsExpression;
+use diesel::expression::bound::Bound;
+use diesel::pg::Pg;
+use diesel::row::Row;
+use diesel::types::{Array, Binary, FromSql, FromSqlRow, HasSqlType, IsNull, NotNull, Nullable, ToSql};
+use diesel::Queryable;
+
+use super::InvalidValueError;
+
+/// Array with `NULL` elements, stored in `Array<Nullable<ST>>` columns
+///
+/// diesel reads arrays with `NULL` elements only into `Vec<Option<T>>` of
+/// types with a generic `Option` implementation and panics when writing
+/// them. This wrapper keeps `NULL` elements at their position in both
+/// directions, for any element type `T` that can be read from and written to
+/// `ST`, including custom types. Use `StrictArray` to reject `NULL` elements
+/// instead.
+///
+/// ```
+/// # #[macro_use] extern crate diesel;
+/// # #[macro_use] extern crate diesel_custom_type;
+/// # use std::error::Error;
+/// use diesel::prelude::*;
+/// use diesel::pg::Pg;
+/// use diesel::types::{Array, FromSql, Nullable, SmallInt, ToSql};
+/// use diesel_custom_type::CustomSqlType;
+/// use diesel_custom_type::adapters::{NullableArray, StrictArray};
+///
+/// #[derive(Debug, Clone, Copy, PartialEq)]
+/// enum Color {
+///     Red = 1,
+///     Green = 2,
+/// }
+///
+/// impl CustomSqlType for Color {
+///     type DataBaseType = SmallInt;
+///     type RawType = i16;
+///     type Error = Box<Error + Send + Sync>;
+///
+///     fn to_database_type(&self) -> i16 {
+///         *self as i16
+///     }
+///
+///     fn from_database_type(v: &i16) -> Result<Self, Self::Error> {
+///         match *v {
+///             1 => Ok(Color::Red),
+///             2 => Ok(Color::Green),
+///             v => Err(format!("Unknown color {}", v).into()),
+///         }
+///     }
+/// }
+///
+/// register_custom_type!(Color);
+///
+/// table! {
+///     palettes {
+///         id -> Integer,
+///         colors -> Array<Nullable<SmallInt>>,
+///     }
+/// }
+///
+/// # fn main() {
+/// type ST = Array<Nullable<SmallInt>>;
+///
+/// let palettes = [
+///     vec![None, Some(Color::Red), Some(Color::Green)],
+///     vec![Some(Color::Red), None, Some(Color::Green)],
+///     vec![Some(Color::Red), Some(Color::Green), None],
+///     vec![None, None],
+///     vec![],
+/// ];
+/// for colors in &palettes {
+///     let colors = NullableArray(colors.clone());
+///     let mut bytes = Vec::new();
+///     ToSql::<ST, Pg>::to_sql(&colors, &mut bytes).unwrap();
+///     let read = <NullableArray<Color> as FromSql<ST, Pg>>::from_sql(Some(&bytes)).unwrap();
+///     assert_eq!(read, colors);
+/// }
+///
+/// let mut bytes = Vec::new();
+/// ToSql::<ST, Pg>::to_sql(&NullableArray(palettes[1].clone()), &mut bytes).unwrap();
+/// let err = <StrictArray<Color> as FromSql<ST, Pg>>::from_sql(Some(&bytes)).unwrap_err();
+/// assert_eq!(err.to_string(), "Invalid value `NULL` for StrictArray: element 2 is NULL");
+///
+/// let header = [1, 1, 21, i32::MAX, 1].iter().flat_map(|v: &i32| v.to_be_bytes()).collect::<Vec<u8>>();
+/// let err = <NullableArray<Color> as FromSql<ST, Pg>>::from_sql(Some(&header)).unwrap_err();
+/// assert_eq!(err.to_string(), "Invalid value `array` for NullableArray: unexpected end of data");
+///
+/// ToSql::<ST, Pg>::to_sql(&StrictArray(vec![Color::Green]), &mut bytes).unwrap();
+/// let _query = palettes::table.filter(palettes::colors.eq(NullableArray(vec![Some(Color::Green), None])));
+/// # }
+/// ```
+///
+/// Round trip through a live database:
+///
+/// ```no_run
+/// # extern crate diesel;
+/// # extern crate diesel_custom_type;
+/// use diesel::{select, Connection, LoadDsl};
+/// use diesel::expression::AsExpression;
+/// use diesel::pg::PgConnection;
+/// use diesel::types::{Array, Nullable, SmallInt};
+/// use diesel_custom_type::adapters::NullableArray;
+///
+/// # fn main() {
+/// let conn = PgConnection::establish(&::std::env::var("DATABASE_URL").unwrap()).unwrap();
+/// for v in &[vec![None, Some(1i16)], vec![Some(1), None, Some(2)], vec![Some(1), None]] {
+///     let v = NullableArray(v.clone());
+///     let expr = AsExpression::<Array<Nullable<SmallInt>>>::as_expression(&v);
+///     assert_eq!(select(expr).get_result::<NullableArray<i16>>(&conn).unwrap(), v);
+/// }
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct NullableArray<T>(pub Vec<Option<T>>);
+
+/// Array stored in `Array<Nullable<ST>>` columns, that fails to read arrays
+/// with `NULL` elements
+///
+/// See `NullableArray` for an example.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct StrictArray<T>(pub Vec<T>);
+
+impl<T> NullableArray<T> {
+    /// Unwraps the contained elements
+    pub fn into_inner(self) -> Vec<Option<T>> {
+        self.0
+    }
+}
+
+impl<T> StrictArray<T> {
+    /// Unwraps the contained elements
+    pub fn into_inner(self) -> Vec<T> {
+        self.0
+    }
+}
+
+impl<T> Deref for NullableArray<T> {
+    type Target = Vec<Option<T>>;
+
+    fn deref(&self) -> &Vec<Option<T>> {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for NullableArray<T> {
+    fn deref_mut(&mut self) -> &mut Vec<Option<T>> {
+        &mut self.0
+    }
+}
+
+impl<T> Deref for StrictArray<T> {
+    type Target = Vec<T>;
+
+    fn deref(&self) -> &Vec<T> {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for StrictArray<T> {
+    fn deref_mut(&mut self) -> &mut Vec<T> {
+        &mut self.0
+    }
+}
+
+impl<T> From<Vec<Option<T>>> for NullableArray<T> {
+    fn from(v: Vec<Option<T>>) -> Self {
+        NullableArray(v)
+    }
+}
+
+impl<T> From<Vec<T>> for StrictArray<T> {
+    fn from(v: Vec<T>) -> Self {
+        StrictArray(v)
+    }
+}
+
+fn invalid(type_name: &'static str, reason: &str) -> Box<dyn Error + Send + Sync> {
+    Box::new(InvalidValueError::new(type_name, "array", reason))
+}
+
+fn read_i32(bytes: &mut &[u8], type_name: &'static str) -> Result<i32, Box<dyn Error + Send + Sync>> {
+    if bytes.len() < 4 {
+        return Err(invalid(type_name, "unexpected end of data"));
+    }
+    let (head, tail) = bytes.split_at(4);
+    *bytes = tail;
+    Ok(i32::from_be_bytes([head[0], head[1], head[2], head[3]]))
+}
+
+// Implemented by hand as diesel panics on `NULL` elements. The binary format
+// is the number of dimensions, a flag for `NULL` elements, the element type
+// oid and the length and lower bound of every dimension, followed by the
+// length prefixed elements. A length of -1 marks a `NULL` element.
+fn write_array<'a, T, ST, W, I>(
+    out: &mut W,
+    len: usize,
+    elements: I,
+    type_name: &'static str,
+) -> Result<IsNull, Box<dyn Error + Send + Sync>>
+where
+    T: ToSql<ST, Pg> + 'a,
+    Pg: HasSqlType<ST>,
+    W: Write,
+    I: Iterator<Item = Option<&'a T>> + Clone,
+{
+    if len > i32::MAX as usize {
+        return Err(invalid(type_name, "array is too large"));
+    }
+    let has_null = elements.clone().any(|e| e.is_none());
+    out.write_all(&1i32.to_be_bytes())?;
+    out.write_all(&(has_null as i32).to_be_bytes())?;
+    out.write_all(&<Pg as HasSqlType<ST>>::metadata().oid.to_be_bytes())?;
+    out.write_all(&(len as i32).to_be_bytes())?;
+    out.write_all(&1i32.to_be_bytes())?;
+
+    let mut buffer = Vec::new();
+    for element in elements {
+        let element = match element {
+            Some(element) => element,
+            None => {
+                out.write_all(&(-1i32).to_be_bytes())?;
+                continue;
+            }
+        };
+        match element.to_sql(&mut buffer)? {
+            IsNull::No if buffer.len() <= i32::MAX as usize => {
+                out.write_all(&(buffer.len() as i32).to_be_bytes())?;
+                out.write_all(&buffer)?;
+            }
+            IsNull::No => return Err(invalid(type_name, "element is too large")),
+            IsNull::Yes => out.write_all(&(-1i32).to_be_bytes())?,
+        }
+        buffer.clear();
+    }
+    Ok(IsNull::No)
+}
+
+fn read_array<T, ST>(bytes: Option<&[u8]>, type_name: &'static str) -> Result<Vec<Option<T>>, Box<dyn Error + Send + Sync>>
+where
+    T: FromSql<ST, Pg>,
+    Pg: HasSqlType<ST>,
+{
+    let mut bytes = match bytes {
+        Some(bytes) => bytes,
+        // reuse diesels error for unexpected `NULL` values
+        None => return <Vec<u8> as FromSql<Binary, Pg>>::from_sql(None).map(|_| Vec::new()),
+    };
+    let dimensions = read_i32(&mut bytes, type_name)?;
+    let _has_null = read_i32(&mut bytes, type_name)?;
+    let _oid = read_i32(&mut bytes, type_name)?;
+    match dimensions {
+        0 => return Ok(Vec::new()),
+        1 => {}
+        _ => return Err(invalid(type_name, "multi-dimensional arrays are not supported")),
+    }
+    let len = read_i32(&mut bytes, type_name)?;
+    let _lower_bound = read_i32(&mut bytes, type_name)?;
+    if len < 0 {
+        return Err(invalid(type_name, "negative number of elements"));
+    }
+
+    // every element takes at least 4 bytes for its length, so a corrupted
+    // count can't reserve more memory than the input justifies
+    let mut elements = Vec::with_capacity((len as usize).min(bytes.len() / 4));
+    for _ in 0..len {
+        let element = match read_i32(&mut bytes, type_name)? {
+            -1 => None,
+            element_len if element_len < 0 || element_len as usize > bytes.len() => {
+                return Err(invalid(type_name, "unexpected end of data"));
+            }
+            element_len => {
+                let (head, tail) = bytes.split_at(element_len as usize);
+                bytes = tail;
+                Some(T::from_sql(Some(head))?)
+            }
+        };
+        elements.push(element);
+    }
+    if !bytes.is_empty() {
+        return Err(invalid(type_name, "unexpected trailing data"));
+    }
+    Ok(elements)
+}
+
+impl<T, ST> ToSql<Array<Nullable<ST>>, Pg> for NullableArray<T>
+where
+    T: ToSql<ST, Pg>,
+    Pg: HasSqlType<ST>,
+    ST: NotNull,
+{
+    fn to_sql<W: Write>(&self, out: &mut W) -> Result<IsNull, Box<dyn Error + Send + Sync>> {
+        write_array(out, self.0.len(), self.0.iter().map(Option::as_ref), "NullableArray")
+    }
+}
+
+impl<T, ST> ToSql<Array<Nullable<ST>>, Pg> for StrictArray<T>
+where
+    T: ToSql<ST, Pg>,
+    Pg: HasSqlType<ST>,
+    ST: NotNull,
+{
+    fn to_sql<W: Write>(&self, out: &mut W) -> Result<IsNull, Box<dyn Error + Send + Sync>> {
+        write_array(out, self.0.len(), self.0.iter().map(Some), "StrictArray")
+    }
+}
+
+impl<T, ST> FromSql<Array<Nullable<ST>>, Pg> for NullableArray<T>
+where
+    T: FromSql<ST, Pg>,
+    Pg: HasSqlType<ST>,
+    ST: NotNull,
+{
+    fn from_sql(bytes: Option<&[u8]>) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        read_array(bytes, "NullableArray").map(NullableArray)
+    }
+}
+
+impl<T, ST> FromSql<Array<Nullable<ST>>, Pg> for StrictArray<T>
+where
+    T: FromSql<ST, Pg>,
+    Pg: HasSqlType<ST>,
+    ST: NotNull,
+{
+    fn from_sql(bytes: Option<&[u8]>) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        read_array(bytes, "StrictArray")?
+            .into_iter()
+            .enumerate()
+            .map(|(i, element)| element.ok_or_else(|| invalid_null(i)))
+            .collect::<Result<_, _>>()
+            .map(StrictArray)
+    }
+}
+
+fn invalid_null(index: usize) -> Box<dyn Error + Send + Sync> {
+    // PostgreSQL arrays start at 1
+    Box::new(InvalidValueError::new("StrictArray", "NULL", format!("element {} is NULL", index + 1)))
+}
+
+macro_rules! array_wrapper {
+    ($Wrapper:ident) => {
+        impl<T, ST> FromSqlRow<Array<Nullable<ST>>, Pg> for $Wrapper<T>
+        where
+            T: FromSql<ST, Pg>,
+            Pg: HasSqlType<ST>,
+            ST: NotNull,
+        {
+            fn build_from_row<R: Row<Pg>>(row: &mut R) -> Result<Self, Box<dyn Error + Send + Sync>> {
+                <$Wrapper<T> as FromSql<Array<Nullable<ST>>, Pg>>::from_sql(row.take())
+            }
+        }
+
+        impl<T, ST> Queryable<Array<Nullable<ST>>, Pg> for $Wrapper<T>
+        where
+            T: FromSql<ST, Pg>,
+            Pg: HasSqlType<ST>,
+            ST: NotNull,
+        {
+            type Row = Self;
+
+            fn build(row: Self) -> Self {
+                row
+            }
+        }
+
+        impl<T, ST> AsExpression<Array<Nullable<ST>>> for $Wrapper<T>
+        where
+            Pg: HasSqlType<ST>,
+            ST: NotNull,
+        {
+            type Expression = Bound<Array<Nullable<ST>>, $Wrapper<T>>;
+
+            fn as_expression(self) -> Self::Expression {
+                Bound::new(self)
+            }
+        }
+
+        impl<'a, T, ST> AsExpression<Array<Nullable<ST>>> for &'a $Wrapper<T>
+        where
+            Pg: HasSqlType<ST>,
+            ST: NotNull,
+        {
+            type Expression = Bound<Array<Nullable<ST>>, &'a $Wrapper<T>>;
+
+            fn as_expression(self) -> Self::Expression {
+                Bound::new(self)
+            }
+        }
+    };
+}
+
+array_wrapper!(NullableArray);
+array_wrapper!(StrictArray);
@@ -0,0 +1,95 @@
+use std::fmt;
+use std::ops::Deref;
+
+use diesel::types::Text;
+
+use CustomSqlType;
+use super::InvalidValueError;
+
+/// Phone number in canonical E.164 form (`+14155550123`) stored in a `Text`
+/// column
+///
+/// A valid number is a plus sign followed by 8 to 15 digits, the first one
+/// not being zero. Reading anything else results in an error. Set `LENIENT`
+/// to `true` (or use [`LenientPhoneE164`](type.LenientPhoneE164.html)) to
+/// strip spaces, dashes and parentheses from stored values before
+/// validating them. Writing always uses the canonical form.
+///
+/// ```
+/// # extern crate diesel;
+/// # extern crate diesel_custom_type;
+/// use diesel_custom_type::CustomSqlType;
+/// use diesel_custom_type::adapters::{LenientPhoneE164, PhoneE164};
+///
+/// # fn main() {
+/// let phone: PhoneE164 = PhoneE164::new("+14155550123").unwrap();
+/// assert_eq!(<PhoneE164>::from_database_type(&phone.to_database_type()).unwrap(), phone);
+///
+/// let legacy = "+1 (415) 555-0123".to_string();
+/// let err = <PhoneE164>::from_database_type(&legacy).unwrap_err();
+/// assert_eq!(err.to_string(),
+///            "Invalid value `+1 (415) 555-0123` for PhoneE164: expected `+` followed by 8 to 15 digits");
+///
+/// let cleaned = LenientPhoneE164::from_database_type(&legacy).unwrap();
+/// assert_eq!(cleaned.to_database_type(), "+14155550123");
+/// assert!(LenientPhoneE164::from_database_type(&"0800 123".to_string()).is_err());
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct PhoneE164<const LENIENT: bool = false>(String);
+
+/// `PhoneE164` that cleans up formatting characters when reading
+pub type LenientPhoneE164 = PhoneE164<true>;
+
+impl<const LENIENT: bool> PhoneE164<LENIENT> {
+    /// Validates `number`, which must already be in canonical form
+    pub fn new(number: &str) -> Result<Self, InvalidValueError> {
+        let digits = number.strip_prefix('+').unwrap_or("");
+        if digits.len() >= 8 && digits.len() <= 15 && !digits.starts_with('0') &&
+           digits.bytes().all(|b| b.is_ascii_digit()) {
+            Ok(PhoneE164(number.to_owned()))
+        } else {
+            Err(InvalidValueError::new("PhoneE164", number, "expected `+` followed by 8 to 15 digits"))
+        }
+    }
+
+    /// Returns the canonical form
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl<const LENIENT: bool> Deref for PhoneE164<LENIENT> {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl<const LENIENT: bool> fmt::Display for PhoneE164<LENIENT> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl<const LENIENT: bool> CustomSqlType for PhoneE164<LENIENT> {
+    type DataBaseType = Text;
+    type RawType = String;
+    type Error = InvalidValueError;
+
+    fn to_database_type(&self) -> String {
+        self.0.clone()
+    }
+
+    fn from_database_type(v: &String) -> Result<Self, InvalidValueError> {
+        if LENIENT {
+            let cleaned = v.chars().filter(|c| !" -()".contains(*c)).collect::<String>();
+            Self::new(&cleaned).map_err(|_| InvalidValueError::new("PhoneE164", v, "expected `+` followed by 8 to 15 digits"))
+        } else {
+            Self::new(v)
+        }
+    }
+}
+
+register_custom_type!(impl[const LENIENT: bool] PhoneE164<LENIENT> where);
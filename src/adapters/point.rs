@@ -0,0 +1,176 @@
+use std::error::Error;
+use std::fmt;
+use std::ops::Deref;
+
+use diesel::types::Text;
+
+use CustomSqlType;
+use super::InvalidValueError;
+
+/// Two dimensional point, `x` is the longitude and `y` the latitude for
+/// geographic coordinates
+///
+/// Only available with the `wkt-point` feature, like the adapters storing it.
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Default)]
+pub struct Point {
+    /// Horizontal coordinate (longitude)
+    pub x: f64,
+    /// Vertical coordinate (latitude)
+    pub y: f64,
+}
+
+impl Point {
+    /// Creates a new point
+    pub fn new(x: f64, y: f64) -> Self {
+        Point { x, y }
+    }
+}
+
+fn parse_coordinate(v: &str) -> Option<f64> {
+    v.trim().parse::<f64>().ok().filter(|v| v.is_finite())
+}
+
+// Reading rejects NaN and infinite coordinates, so writing them fails too
+fn check_finite(point: &Point, type_name: &'static str, raw: String) -> Result<String, Box<dyn Error + Send + Sync>> {
+    if point.x.is_finite() && point.y.is_finite() {
+        Ok(raw)
+    } else {
+        Err(Box::new(InvalidValueError::new(type_name, raw, "coordinates must be finite")))
+    }
+}
+
+/// [`Point`](struct.Point.html) stored as WKT (`POINT(30.5 50.4)`) in a
+/// `Text` column
+///
+/// Reading is case insensitive and tolerates extra whitespace. Other
+/// geometries and malformed numbers result in an error. NaN and infinite
+/// coordinates can't be read, writing them fails as well.
+///
+/// ```
+/// # extern crate diesel;
+/// # extern crate diesel_custom_type;
+/// use diesel_custom_type::CustomSqlType;
+/// use diesel_custom_type::adapters::{LatLng, Point, WktPoint};
+///
+/// # fn main() {
+/// let point = WktPoint(Point::new(30.5, -50.4));
+/// assert_eq!(point.to_database_type(), "POINT(30.5 -50.4)");
+/// assert_eq!(WktPoint::from_database_type(&point.to_database_type()).unwrap(), point);
+/// assert_eq!(WktPoint::from_database_type(&" point ( 30.5   -50.4 ) ".to_string()).unwrap(), point);
+///
+/// let err = WktPoint::from_database_type(&"LINESTRING(30 10, 10 30)".to_string()).unwrap_err();
+/// assert_eq!(err.to_string(), "Invalid value `LINESTRING(30 10, 10 30)` for WktPoint: expected a POINT geometry");
+/// assert!(WktPoint::from_database_type(&"POINT(30.5 abc)".to_string()).is_err());
+/// assert!(WktPoint::from_database_type(&"POINT(1 2 3)".to_string()).is_err());
+///
+/// let err = WktPoint(Point::new(f64::NAN, 1.0)).try_to_database_type().unwrap_err();
+/// assert_eq!(err.to_string(), "Invalid value `POINT(NaN 1)` for WktPoint: coordinates must be finite");
+/// assert!(WktPoint::from_database_type(&"POINT(NaN 1)".to_string()).is_err());
+///
+/// let old = LatLng(Point::new(30.5, -50.4));
+/// assert_eq!(old.to_database_type(), "-50.4,30.5");
+/// assert_eq!(LatLng::from_database_type(&" -50.4 , 30.5".to_string()).unwrap(), old);
+/// let err = LatLng::from_database_type(&"-50.4;30.5".to_string()).unwrap_err();
+/// assert_eq!(err.to_string(), "Invalid value `-50.4;30.5` for LatLng: expected `lat,lng`");
+/// assert!(LatLng(Point::new(1.0, f64::INFINITY)).try_to_database_type().is_err());
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Default)]
+pub struct WktPoint(pub Point);
+
+impl Deref for WktPoint {
+    type Target = Point;
+
+    fn deref(&self) -> &Point {
+        &self.0
+    }
+}
+
+impl From<Point> for WktPoint {
+    fn from(v: Point) -> Self {
+        WktPoint(v)
+    }
+}
+
+impl fmt::Display for WktPoint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "POINT({} {})", self.0.x, self.0.y)
+    }
+}
+
+impl CustomSqlType for WktPoint {
+    type DataBaseType = Text;
+    type RawType = String;
+    type Error = InvalidValueError;
+
+    fn to_database_type(&self) -> String {
+        self.to_string()
+    }
+
+    fn try_to_database_type(&self) -> Result<String, Box<dyn Error + Send + Sync>> {
+        check_finite(&self.0, "WktPoint", self.to_database_type())
+    }
+
+    fn from_database_type(v: &String) -> Result<Self, InvalidValueError> {
+        let trimmed = v.trim();
+        let body = match trimmed.get(..5) {
+            Some(keyword) if keyword.eq_ignore_ascii_case("POINT") => trimmed[5..].trim_start(),
+            _ => return Err(InvalidValueError::new("WktPoint", v, "expected a POINT geometry")),
+        };
+        if !body.starts_with('(') || !body.ends_with(')') {
+            return Err(InvalidValueError::new("WktPoint", v, "expected `POINT(x y)`"));
+        }
+        let coordinates = body[1..body.len() - 1].split_whitespace().map(parse_coordinate).collect::<Vec<_>>();
+        match coordinates[..] {
+            [Some(x), Some(y)] => Ok(WktPoint(Point::new(x, y))),
+            _ => Err(InvalidValueError::new("WktPoint", v, "expected two finite coordinates")),
+        }
+    }
+}
+
+register_custom_type!(WktPoint);
+
+/// [`Point`](struct.Point.html) stored as `"lat,lng"` in a `Text` column
+///
+/// Reading tolerates whitespace around the coordinates. Like `WktPoint`,
+/// NaN and infinite coordinates can neither be read nor written.
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Default)]
+pub struct LatLng(pub Point);
+
+impl Deref for LatLng {
+    type Target = Point;
+
+    fn deref(&self) -> &Point {
+        &self.0
+    }
+}
+
+impl From<Point> for LatLng {
+    fn from(v: Point) -> Self {
+        LatLng(v)
+    }
+}
+
+impl CustomSqlType for LatLng {
+    type DataBaseType = Text;
+    type RawType = String;
+    type Error = InvalidValueError;
+
+    fn to_database_type(&self) -> String {
+        format!("{},{}", self.0.y, self.0.x)
+    }
+
+    fn try_to_database_type(&self) -> Result<String, Box<dyn Error + Send + Sync>> {
+        check_finite(&self.0, "LatLng", self.to_database_type())
+    }
+
+    fn from_database_type(v: &String) -> Result<Self, InvalidValueError> {
+        let coordinates = v.split(',').map(parse_coordinate).collect::<Vec<_>>();
+        match coordinates[..] {
+            [Some(lat), Some(lng)] => Ok(LatLng(Point::new(lng, lat))),
+            _ => Err(InvalidValueError::new("LatLng", v, "expected `lat,lng`")),
+        }
+    }
+}
+
+register_custom_type!(LatLng);
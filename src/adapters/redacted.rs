@@ -0,0 +1,160 @@
+use std::convert::Infallible;
+use std::error::Error;
+use std::fmt;
+use std::io::Write;
+
+use diesel::backend::Backend;
+use diesel::expression::AsExpression;
+use diesel::expression::bound::Bound;
+use diesel::Queryable;
+use diesel::row::Row;
+use diesel::types::{FromSql, FromSqlRow, HasSqlType, IsNull, Text, ToSql};
+
+use CustomSqlType;
+
+/// Text column holding a secret (API tokens, passwords, ...) that must never
+/// be printed by accident
+///
+/// The `Debug` implementation always prints `[REDACTED]` and there is no
+/// `Display` implementation; the value is only accessible through
+/// [`expose_secret`](#method.expose_secret). Errors produced while reading
+/// the column never contain the stored value.
+///
+/// ```
+/// # extern crate diesel;
+/// # extern crate diesel_custom_type;
+/// use diesel::pg::Pg;
+/// use diesel::types::{FromSql, Text};
+/// use diesel_custom_type::adapters::Redacted;
+///
+/// # fn main() {
+/// let token = Redacted::new("secret-token".to_string());
+/// assert_eq!(format!("{:?}", token), "[REDACTED]");
+/// assert_eq!(format!("{:?}", Some(&token)), "Some([REDACTED])");
+/// assert_eq!(token.expose_secret(), "secret-token");
+///
+/// let err = <Redacted as FromSql<Text, Pg>>::from_sql(Some(b"secret-token\xff")).unwrap_err();
+/// assert!(!err.to_string().contains("secret"));
+/// assert!(!format!("{:?}", err).contains("115, 101, 99"));
+/// # }
+/// ```
+#[derive(Clone, Default)]
+pub struct Redacted(String);
+
+impl Redacted {
+    /// Wraps a secret value
+    pub fn new(secret: String) -> Self {
+        Redacted(secret)
+    }
+
+    /// Gives access to the secret value
+    pub fn expose_secret(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Debug for Redacted {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("[REDACTED]")
+    }
+}
+
+impl From<String> for Redacted {
+    fn from(secret: String) -> Self {
+        Redacted(secret)
+    }
+}
+
+/// Error returned if a `Redacted` column could not be read
+///
+/// Neither `Display` nor `Debug` include the stored value
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RedactedValueError;
+
+impl fmt::Display for RedactedValueError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("Invalid value found for a redacted column")
+    }
+}
+
+impl Error for RedactedValueError {
+    fn description(&self) -> &str {
+        "Invalid value found for a redacted column"
+    }
+}
+
+impl CustomSqlType for Redacted {
+    type DataBaseType = Text;
+    type RawType = String;
+    type Error = Infallible;
+
+    fn to_database_type(&self) -> String {
+        self.0.clone()
+    }
+
+    fn from_database_type(v: &String) -> Result<Self, Infallible> {
+        Ok(Redacted(v.clone()))
+    }
+}
+
+// Implemented by hand instead of using `register_custom_type!` because the
+// errors of the underlying `String` conversion may contain the raw value.
+impl<DB> ToSql<Text, DB> for Redacted
+    where DB: Backend + HasSqlType<Text>,
+          String: ToSql<Text, DB>
+{
+    fn to_sql<W: Write>(&self, out: &mut W) -> Result<IsNull, Box<dyn Error + Send + Sync>> {
+        ToSql::<Text, DB>::to_sql(&self.0, out).map_err(|_| Box::new(RedactedValueError) as Box<dyn Error + Send + Sync>)
+    }
+}
+
+impl<DB> FromSql<Text, DB> for Redacted
+    where DB: Backend + HasSqlType<Text>,
+          String: FromSql<Text, DB>
+{
+    fn from_sql(bytes: Option<&DB::RawValue>) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        match bytes {
+            // keep diesels error for unexpected `NULL` values, it contains no payload
+            None => String::from_sql(None).map(Redacted),
+            Some(_) => String::from_sql(bytes)
+                .map(Redacted)
+                .map_err(|_| Box::new(RedactedValueError) as Box<dyn Error + Send + Sync>),
+        }
+    }
+}
+
+impl<DB> FromSqlRow<Text, DB> for Redacted
+    where DB: Backend + HasSqlType<Text>,
+          Redacted: FromSql<Text, DB>
+{
+    fn build_from_row<R: Row<DB>>(row: &mut R) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        Redacted::from_sql(row.take())
+    }
+}
+
+impl<DB> Queryable<Text, DB> for Redacted
+    where DB: Backend + HasSqlType<Text>,
+          Redacted: FromSqlRow<Text, DB>
+{
+    type Row = Self;
+
+    fn build(row: Self) -> Self {
+        row
+    }
+}
+
+impl AsExpression<Text> for Redacted {
+    type Expression = Bound<Text, Redacted>;
+
+    fn as_expression(self) -> Self::Expression {
+        Bound::new(self)
+    }
+}
+
+impl<'a> AsExpression<Text> for &'a Redacted {
+    type Expression = Bound<Text, &'a Redacted>;
+
+    fn as_expression(self) -> Self::Expression {
+        Bound::new(self)
+    }
+}
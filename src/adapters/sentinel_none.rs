@@ -0,0 +1,197 @@
+use std::error::Error;
+use std::ops::Deref;
+
+use super::InvalidValueError;
+use CustomSqlType;
+
+/// Wrapper for integer backed custom types stored in legacy columns that use
+/// a sentinel value instead of `NULL` to mark absent values
+///
+/// Reading `SENTINEL` yields `SentinelNone(None)`, every other value is
+/// converted using `T::from_database_type`, so errors for unknown values are
+/// reported as before. Writing `SentinelNone(None)` stores `SENTINEL`, the
+/// column itself stays `NOT NULL`.
+///
+/// A `SENTINEL` that does not fit into `T::RawType` is rejected at compile
+/// time, see [`SentinelRaw`]. Writing a `Some` value that is stored as
+/// `SENTINEL` fails, as it would read back as `None`. `to_database_type`
+/// can't report that and writes the value unchanged.
+///
+/// ```
+/// # extern crate diesel;
+/// # extern crate diesel_custom_type;
+/// # use std::error::Error;
+/// use diesel::types::SmallInt;
+/// use diesel_custom_type::CustomSqlType;
+/// use diesel_custom_type::adapters::SentinelNone;
+///
+/// #[derive(Debug, Clone, Copy, PartialEq)]
+/// enum Color {
+///     Red = 1,
+///     Green = 2,
+/// }
+///
+/// impl CustomSqlType for Color {
+///     type DataBaseType = SmallInt;
+///     type RawType = i16;
+///     type Error = Box<dyn Error + Send + Sync>;
+///
+///     fn to_database_type(&self) -> i16 {
+///         *self as i16
+///     }
+///
+///     fn from_database_type(v: &i16) -> Result<Self, Box<dyn Error + Send + Sync>> {
+///         match *v {
+///             1 => Ok(Color::Red),
+///             2 => Ok(Color::Green),
+///             v => Err(format!("Unknown value {} for Color found", v).into()),
+///         }
+///     }
+/// }
+///
+/// # fn main() {
+/// type LegacyColor = SentinelNone<Color, -1>;
+///
+/// assert_eq!(LegacyColor::from_database_type(&-1).unwrap(), SentinelNone(None));
+/// assert_eq!(SentinelNone::<Color, -1>(None).to_database_type(), -1);
+/// assert_eq!(LegacyColor::from_database_type(&2).unwrap(), SentinelNone(Some(Color::Green)));
+/// assert_eq!(SentinelNone::<Color, -1>(Some(Color::Red)).to_database_type(), 1);
+///
+/// let err = LegacyColor::from_database_type(&7).unwrap_err();
+/// assert_eq!(err.to_string(), "Unknown value 7 for Color found");
+///
+/// let err = SentinelNone::<Color, 2>(Some(Color::Green)).try_to_database_type().unwrap_err();
+/// assert_eq!(err.to_string(), "Invalid value `2` for SentinelNone: value is the sentinel");
+/// # }
+/// ```
+///
+/// `SmallInt` columns can't store `40000`:
+///
+/// ```compile_fail
+/// # extern crate diesel;
+/// # extern crate diesel_custom_type;
+/// # use std::error::Error;
+/// # use diesel::types::SmallInt;
+/// # use diesel_custom_type::CustomSqlType;
+/// # use diesel_custom_type::adapters::SentinelNone;
+/// # #[derive(Debug, Clone, Copy, PartialEq)]
+/// # struct Color;
+/// # impl CustomSqlType for Color {
+/// #     type DataBaseType = SmallInt;
+/// #     type RawType = i16;
+/// #     type Error = Box<dyn Error + Send + Sync>;
+/// #     fn to_database_type(&self) -> i16 { 1 }
+/// #     fn from_database_type(_: &i16) -> Result<Self, Box<dyn Error + Send + Sync>> { Ok(Color) }
+/// # }
+/// # fn main() {
+/// SentinelNone::<Color, 40000>(None).to_database_type();
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct SentinelNone<T, const SENTINEL: i64>(pub Option<T>);
+
+/// Integer raw types `SentinelNone` can store its sentinel in
+pub trait SentinelRaw: Copy + Into<i64> {
+    /// Smallest value of the type
+    const MIN: i64;
+    /// Largest value of the type
+    const MAX: i64;
+
+    /// Converts a value between `MIN` and `MAX`
+    fn from_sentinel(v: i64) -> Self;
+}
+
+macro_rules! sentinel_raw {
+    ($($ty:ty),*) => {$(
+        impl SentinelRaw for $ty {
+            const MIN: i64 = <$ty>::MIN as i64;
+            const MAX: i64 = <$ty>::MAX as i64;
+
+            fn from_sentinel(v: i64) -> Self {
+                v as $ty
+            }
+        }
+    )*};
+}
+
+sentinel_raw!(i16, i32, i64, u32);
+
+impl<T, const SENTINEL: i64> SentinelNone<T, SENTINEL>
+    where T: CustomSqlType,
+          T::RawType: SentinelRaw
+{
+    // Evaluated once the impl is used with a concrete type, which makes an
+    // out of range sentinel a compile error
+    const SENTINEL_FITS: () = assert!(
+        SENTINEL >= <T::RawType as SentinelRaw>::MIN && SENTINEL <= <T::RawType as SentinelRaw>::MAX,
+        "sentinel does not fit into the raw type"
+    );
+
+    fn sentinel() -> T::RawType {
+        #[allow(clippy::let_unit_value)]
+        let () = Self::SENTINEL_FITS;
+        T::RawType::from_sentinel(SENTINEL)
+    }
+}
+
+impl<T, const SENTINEL: i64> SentinelNone<T, SENTINEL> {
+    /// Unwraps the contained optional value
+    pub fn into_inner(self) -> Option<T> {
+        self.0
+    }
+}
+
+impl<T, const SENTINEL: i64> Deref for SentinelNone<T, SENTINEL> {
+    type Target = Option<T>;
+
+    fn deref(&self) -> &Option<T> {
+        &self.0
+    }
+}
+
+impl<T, const SENTINEL: i64> From<Option<T>> for SentinelNone<T, SENTINEL> {
+    fn from(v: Option<T>) -> Self {
+        SentinelNone(v)
+    }
+}
+
+impl<T, const SENTINEL: i64> CustomSqlType for SentinelNone<T, SENTINEL>
+    where T: CustomSqlType,
+          T::RawType: SentinelRaw
+{
+    type DataBaseType = T::DataBaseType;
+    type RawType = T::RawType;
+    type Error = T::Error;
+
+    fn to_database_type(&self) -> T::RawType {
+        match self.0 {
+            Some(ref v) => v.to_database_type(),
+            None => Self::sentinel(),
+        }
+    }
+
+    fn try_to_database_type(&self) -> Result<T::RawType, Box<dyn Error + Send + Sync>> {
+        match self.0 {
+            Some(ref v) => {
+                let raw = v.try_to_database_type()?;
+                if raw.into() == SENTINEL {
+                    return Err(Box::new(InvalidValueError::new("SentinelNone", SENTINEL, "value is the sentinel")));
+                }
+                Ok(raw)
+            }
+            None => Ok(Self::sentinel()),
+        }
+    }
+
+    fn from_database_type(v: &T::RawType) -> Result<Self, T::Error> {
+        if (*v).into() == Self::sentinel().into() {
+            Ok(SentinelNone(None))
+        } else {
+            T::from_database_type(v).map(|v| SentinelNone(Some(v)))
+        }
+    }
+}
+
+register_custom_type!(impl[T, const SENTINEL: i64] SentinelNone<T, SENTINEL>
+                      where T: CustomSqlType,
+                            T::RawType: SentinelRaw);
@@ -0,0 +1,586 @@
+//! Generate custom types from a mapping file in a build script
+//!
+//! The mapping file uses a small subset of TOML: one table per type, with a
+//! `sql_type` key followed by one `Variant = value` pair per variant.
+//! Integer SQL types take integer values, text types take quoted strings.
+//! A variant that was stored with different values over time takes an array
+//! instead: all values are read as that variant, the first one is written.
+//!
+//! ```toml
+//! [Color]
+//! sql_type = "SmallInt"
+//! Red = 1
+//! Green = 2
+//!
+//! [Status]
+//! sql_type = "Text"
+//! Active = "active"
+//! Blocked = "blocked"
+//! Archived = ["archived", "deleted"]
+//! ```
+//!
+//! `generate_custom_types` turns this into enums with `CustomSqlType` and
+//! `Variants` implementations, registered with `register_custom_type!`.
+//! Write the result to `OUT_DIR` from `build.rs`:
+//!
+//! ```ignore
+//! extern crate diesel_custom_type;
+//!
+//! use std::{env, fs, path::Path};
+//!
+//! fn main() {
+//!     println!("cargo:rerun-if-changed=mappings.toml");
+//!     let input = fs::read_to_string("mappings.toml").unwrap();
+//!     let code = diesel_custom_type::codegen::generate_custom_types(&input)
+//!         .unwrap_or_else(|e| panic!("mappings.toml: {}", e));
+//!     let out = Path::new(&env::var("OUT_DIR").unwrap()).join("custom_types.rs");
+//!     fs::write(out, code).unwrap();
+//! }
+//! ```
+//!
+//! and include it in a crate that uses `#[macro_use] extern crate
+//! diesel_custom_type;`:
+//!
+//! ```ignore
+//! include!(concat!(env!("OUT_DIR"), "/custom_types.rs"));
+//! ```
+
+use std::error::Error;
+use std::fmt;
+use std::fmt::Write;
+
+/// Error returned by `generate_custom_types` for malformed mapping files
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CodegenError {
+    /// Line of the offending entry, starting at 1
+    pub line: usize,
+    /// What is wrong with the entry
+    pub reason: String,
+}
+
+impl fmt::Display for CodegenError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.reason)
+    }
+}
+
+impl Error for CodegenError {
+    fn description(&self) -> &str {
+        "Invalid custom type mapping"
+    }
+}
+
+#[derive(PartialEq)]
+enum Value {
+    Int(i64),
+    Str(String),
+}
+
+struct Mapping {
+    line: usize,
+    name: String,
+    sql_type: Option<&'static SqlType>,
+    /// Variant names with their values, the first value is the one written
+    variants: Vec<(String, Vec<Value>)>,
+}
+
+struct SqlType {
+    name: &'static str,
+    raw_type: &'static str,
+    /// Value range for integer types, `None` for text types
+    range: Option<(i64, i64)>,
+}
+
+/// SQL types supported by the mapping file
+const SQL_TYPES: &[SqlType] = &[
+    SqlType { name: "SmallInt", raw_type: "i16", range: Some((i16::MIN as i64, i16::MAX as i64)) },
+    SqlType { name: "Integer", raw_type: "i32", range: Some((i32::MIN as i64, i32::MAX as i64)) },
+    SqlType { name: "BigInt", raw_type: "i64", range: Some((i64::MIN, i64::MAX)) },
+    SqlType { name: "Text", raw_type: "String", range: None },
+    SqlType { name: "VarChar", raw_type: "String", range: None },
+];
+
+/// Generates Rust source code for the types described by `input`
+///
+/// ```
+/// use diesel_custom_type::codegen::generate_custom_types;
+///
+/// let code = generate_custom_types("\
+/// [Color]
+/// sql_type = \"SmallInt\"
+/// Red = 1
+/// Green = 2
+/// ").unwrap();
+/// assert!(code.contains("pub enum Color {"));
+/// assert!(code.contains("Color::Green => 2,"));
+/// assert!(code.contains("register_custom_type!(Color);"));
+///
+/// let err = generate_custom_types("\
+/// [Color]
+/// sql_type = \"SmallInt\"
+/// Red = 1
+/// Green = \"green\"
+/// ").unwrap_err();
+/// assert_eq!(err.to_string(), "line 4: `Color::Green` needs an integer value for `SmallInt`");
+/// ```
+///
+/// Types stored as integers additionally get `const fn to_raw` and
+/// `const fn from_raw` methods, which the `CustomSqlType` implementation
+/// delegates to. They can be used to build lookup tables or to check the
+/// mapping at compile time. The `RAW_VALUES` and `ACCEPTED_RAW_VALUES`
+/// constants list the values written and the values read, for example to
+/// build a `CHECK` constraint:
+///
+/// ```
+/// # #[macro_use] extern crate diesel_custom_type;
+/// # extern crate diesel;
+/// use diesel_custom_type::{CustomSqlType, Variants};
+/// use diesel_custom_type::codegen::generate_custom_types;
+///
+/// // generated from the mapping in `main`
+/// include!(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/codegen_priority.rs"));
+///
+/// const DEFAULT: Priority = match Priority::from_raw(5) {
+///     Some(v) => v,
+///     None => panic!("no priority stored as 5"),
+/// };
+///
+/// static BY_RAW: [Option<Priority>; 11] = {
+///     let mut table = [None; 11];
+///     let mut raw = 0;
+///     while raw < 11 {
+///         table[raw] = Priority::from_raw(raw as i16);
+///         raw += 1;
+///     }
+///     table
+/// };
+///
+/// fn main() {
+///     let code = generate_custom_types("\
+/// [Priority]
+/// sql_type = \"SmallInt\"
+/// Low = 1
+/// Normal = 5
+/// High = 10
+/// ").unwrap();
+///     assert_eq!(code, include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/codegen_priority.rs")));
+///
+///     assert_eq!(DEFAULT, Priority::Normal);
+///     assert_eq!(BY_RAW[10], Some(Priority::High));
+///     assert_eq!(BY_RAW[2], None);
+///     for v in Priority::variants() {
+///         assert_eq!(v.to_raw(), v.to_database_type());
+///         assert_eq!(Priority::from_raw(v.to_raw()), Some(*v));
+///         assert_eq!(Priority::from_database_type(&v.to_raw()).unwrap(), *v);
+///     }
+///     assert!(Priority::from_database_type(&2).is_err());
+///     assert_eq!(Priority::RAW_VALUES, [1, 5, 10]);
+///     assert_eq!(Priority::ACCEPTED_RAW_VALUES, Priority::RAW_VALUES);
+/// }
+/// ```
+///
+/// Values given as an array are aliases: all of them are read as the
+/// variant, the first one is written. A value can only belong to one
+/// variant. Besides rejecting such mappings, the generated code checks at
+/// compile time that no value is read as two variants and, for integer
+/// types, that every variant is read back as itself:
+///
+/// ```
+/// # #[macro_use] extern crate diesel_custom_type;
+/// # extern crate diesel;
+/// use diesel_custom_type::CustomSqlType;
+/// use diesel_custom_type::codegen::generate_custom_types;
+///
+/// // generated from the mapping in `main`
+/// include!(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/codegen_state.rs"));
+///
+/// fn main() {
+///     let code = generate_custom_types("\
+/// [State]
+/// sql_type = \"SmallInt\"
+/// Active = 1
+/// Archived = [2, 7, 9]
+/// ").unwrap();
+///     assert_eq!(code, include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/codegen_state.rs")));
+///
+///     for raw in &[2, 7, 9] {
+///         assert_eq!(State::from_database_type(raw).unwrap(), State::Archived);
+///     }
+///     assert_eq!(State::Archived.to_database_type(), 2);
+///     assert_eq!(State::RAW_VALUES, [1, 2]);
+///     assert_eq!(State::ACCEPTED_RAW_VALUES, [1, 2, 7, 9]);
+///
+///     let err = generate_custom_types("\
+/// [State]
+/// sql_type = \"SmallInt\"
+/// Active = [1, 7]
+/// Archived = [2, 7, 9]
+/// ").unwrap_err();
+///     assert_eq!(err.to_string(), "line 4: `State::Archived` uses the value 7 of `State::Active`");
+/// }
+/// ```
+///
+/// Comments start with `#` and run to the end of the line, a `#` inside a
+/// string is part of the value:
+///
+/// ```
+/// # #[macro_use] extern crate diesel_custom_type;
+/// # extern crate diesel;
+/// use diesel_custom_type::CustomSqlType;
+/// use diesel_custom_type::codegen::generate_custom_types;
+///
+/// // generated from the mapping in `main`
+/// include!(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/codegen_tag.rs"));
+///
+/// fn main() {
+///     let code = generate_custom_types("\
+/// ## tags shown in the issue tracker
+/// [Tag]
+/// sql_type = \"Text\"
+/// Active = \"active\" # legacy
+/// Urgent = [\"#urgent\", \"urgent\"] # renamed in 2.0
+/// ").unwrap();
+///     assert_eq!(code, include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/codegen_tag.rs")));
+///
+///     assert_eq!(Tag::Active.to_database_type(), "active");
+///     assert_eq!(Tag::Urgent.to_database_type(), "#urgent");
+///     assert_eq!(Tag::from_database_type(&"urgent".to_string()).unwrap(), Tag::Urgent);
+/// }
+/// ```
+pub fn generate_custom_types(input: &str) -> Result<String, CodegenError> {
+    let mappings = parse(input)?;
+    let mut out = String::from("// Generated by diesel_custom_type::codegen, do not edit\n");
+    for m in &mappings {
+        write_mapping(&mut out, m).expect("Writing to a string never fails");
+    }
+    Ok(out)
+}
+
+fn error<T>(line: usize, reason: String) -> Result<T, CodegenError> {
+    Err(CodegenError { line, reason })
+}
+
+fn is_ident(s: &str) -> bool {
+    let mut chars = s.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn parse_value(s: &str, line: usize) -> Result<Value, CodegenError> {
+    if s.len() >= 2 && s.starts_with('"') && s.ends_with('"') {
+        let inner = &s[1..s.len() - 1];
+        if inner.contains('"') || inner.contains('\\') {
+            return error(line, "escapes are not supported in strings".into());
+        }
+        Ok(Value::Str(inner.into()))
+    } else {
+        match s.replace('_', "").parse() {
+            Ok(v) => Ok(Value::Int(v)),
+            Err(_) => error(line, format!("expected an integer or a quoted string, found `{}`", s)),
+        }
+    }
+}
+
+fn parse_values(s: &str, line: usize) -> Result<Vec<Value>, CodegenError> {
+    let inner = match s.strip_prefix('[') {
+        Some(rest) => match rest.strip_suffix(']') {
+            Some(inner) => inner,
+            None => return error(line, "expected `]` after the values".into()),
+        },
+        None => return Ok(vec![parse_value(s, line)?]),
+    };
+    // strings can't contain escaped quotes, so a comma outside of a string
+    // always separates two values
+    let mut values = Vec::new();
+    let (mut start, mut in_string) = (0, false);
+    for (pos, c) in inner.char_indices() {
+        match c {
+            '"' => in_string = !in_string,
+            ',' if !in_string => {
+                values.push(parse_value(inner[start..pos].trim(), line)?);
+                start = pos + 1;
+            }
+            _ => {}
+        }
+    }
+    let last = inner[start..].trim();
+    if !last.is_empty() {
+        values.push(parse_value(last, line)?);
+    }
+    if values.is_empty() {
+        return error(line, "expected at least one value".into());
+    }
+    Ok(values)
+}
+
+fn finish(m: &Mapping) -> Result<(), CodegenError> {
+    if m.sql_type.is_none() {
+        return error(m.line, format!("`{}` has no `sql_type`", m.name));
+    }
+    if m.variants.is_empty() {
+        return error(m.line, format!("`{}` has no variants", m.name));
+    }
+    Ok(())
+}
+
+// A `#` starts a comment unless it is part of a string
+fn strip_comment(line: &str) -> &str {
+    let mut in_string = false;
+    for (pos, c) in line.char_indices() {
+        match c {
+            '"' => in_string = !in_string,
+            '#' if !in_string => return &line[..pos],
+            _ => {}
+        }
+    }
+    line
+}
+
+fn parse(input: &str) -> Result<Vec<Mapping>, CodegenError> {
+    let mut mappings: Vec<Mapping> = Vec::new();
+    for (i, line) in input.lines().enumerate() {
+        let line_no = i + 1;
+        let line = strip_comment(line).trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        if line.starts_with('[') {
+            let name = match line.strip_suffix(']') {
+                Some(name) => name[1..].trim(),
+                None => return error(line_no, "expected `]` after the type name".into()),
+            };
+            if !is_ident(name) {
+                return error(line_no, format!("`{}` is not a valid type name", name));
+            }
+            if mappings.iter().any(|m| m.name == name) {
+                return error(line_no, format!("`{}` is defined twice", name));
+            }
+            if let Some(m) = mappings.last() {
+                finish(m)?;
+            }
+            mappings.push(Mapping {
+                line: line_no,
+                name: name.into(),
+                sql_type: None,
+                variants: Vec::new(),
+            });
+            continue;
+        }
+
+        let (key, value) = match line.find('=') {
+            Some(pos) => (line[..pos].trim(), line[pos + 1..].trim()),
+            None => return error(line_no, "expected `key = value`".into()),
+        };
+        let m = match mappings.last_mut() {
+            Some(m) => m,
+            None => return error(line_no, "expected a `[TypeName]` header first".into()),
+        };
+        let mut values = parse_values(value, line_no)?;
+
+        if key == "sql_type" {
+            let name = match (values.pop(), values.is_empty()) {
+                (Some(Value::Str(name)), true) => name,
+                _ => return error(line_no, "`sql_type` must be a string".into()),
+            };
+            if !m.variants.is_empty() || m.sql_type.is_some() {
+                return error(line_no, "`sql_type` must be given once, before the variants".into());
+            }
+            match SQL_TYPES.iter().find(|t| t.name == name) {
+                Some(t) => m.sql_type = Some(t),
+                None => return error(line_no, format!("unsupported sql type `{}`", name)),
+            }
+            continue;
+        }
+
+        if !is_ident(key) {
+            return error(line_no, format!("`{}` is not a valid variant name", key));
+        }
+        let sql_type = match m.sql_type {
+            Some(sql_type) => sql_type,
+            None => return error(line_no, "expected `sql_type` before the variants".into()),
+        };
+        for value in &values {
+            match (sql_type.range, value) {
+                (Some((min, max)), &Value::Int(v)) if v < min || v > max => {
+                    return error(line_no, format!("`{}::{}` value {} does not fit into `{}`", m.name, key, v, sql_type.name));
+                }
+                (Some(_), &Value::Str(_)) => {
+                    return error(line_no, format!("`{}::{}` needs an integer value for `{}`", m.name, key, sql_type.name));
+                }
+                (None, &Value::Int(_)) => {
+                    return error(line_no, format!("`{}::{}` needs a string value for `{}`", m.name, key, sql_type.name));
+                }
+                _ => {}
+            }
+        }
+        if m.variants.iter().any(|v| v.0 == key) {
+            return error(line_no, format!("`{}::{}` is defined twice", m.name, key));
+        }
+        for (i, value) in values.iter().enumerate() {
+            if values[..i].contains(value) {
+                return error(line_no, format!("`{}::{}` lists the value {} twice", m.name, key, literal(value)));
+            }
+            if let Some(other) = m.variants.iter().find(|v| v.1.contains(value)) {
+                let (name, other) = (&m.name, &other.0);
+                return error(line_no, format!("`{}::{}` uses the value {} of `{}::{}`", name, key, literal(value), name, other));
+            }
+        }
+        m.variants.push((key.into(), values));
+    }
+    match mappings.last() {
+        Some(m) => finish(m)?,
+        None => return error(1, "no types defined".into()),
+    }
+    Ok(mappings)
+}
+
+fn literal(v: &Value) -> String {
+    match *v {
+        Value::Int(v) => v.to_string(),
+        Value::Str(ref v) => format!("{:?}", v),
+    }
+}
+
+/// All values of a variant as a match pattern
+fn pattern(values: &[Value]) -> String {
+    values.iter().map(literal).collect::<Vec<_>>().join(" | ")
+}
+
+fn write_mapping(out: &mut String, m: &Mapping) -> fmt::Result {
+    let sql_type = m.sql_type.expect("Checked while parsing");
+    let (sql_type, raw_type) = (sql_type.name, sql_type.raw_type);
+    let is_text = raw_type == "String";
+    let slice_type = if is_text { "&str" } else { raw_type };
+    let written = m.variants.iter().map(|v| literal(&v.1[0])).collect::<Vec<_>>();
+    let accepted = m.variants.iter().flat_map(|v| v.1.iter().map(literal)).collect::<Vec<_>>();
+
+    writeln!(out)?;
+    writeln!(out, "#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]")?;
+    writeln!(out, "pub enum {} {{", m.name)?;
+    for v in &m.variants {
+        writeln!(out, "    {},", v.0)?;
+    }
+    writeln!(out, "}}")?;
+    writeln!(out)?;
+
+    writeln!(out, "impl {} {{", m.name)?;
+    writeln!(out, "    /// Values written to the database, in declaration order")?;
+    writeln!(out, "    pub const RAW_VALUES: &[{}] = &[{}];", slice_type, written.join(", "))?;
+    writeln!(out)?;
+    writeln!(out, "    /// Values read from the database, including aliases")?;
+    writeln!(out, "    pub const ACCEPTED_RAW_VALUES: &[{}] = &[{}];", slice_type, accepted.join(", "))?;
+    if !is_text {
+        writeln!(out)?;
+        writeln!(out, "    /// Returns the value stored in the database")?;
+        writeln!(out, "    pub const fn to_raw(self) -> {} {{", raw_type)?;
+        writeln!(out, "        match self {{")?;
+        for v in &m.variants {
+            writeln!(out, "            {}::{} => {},", m.name, v.0, literal(&v.1[0]))?;
+        }
+        writeln!(out, "        }}")?;
+        writeln!(out, "    }}")?;
+        writeln!(out)?;
+        writeln!(out, "    /// Returns the variant stored as `raw`, if any")?;
+        writeln!(out, "    pub const fn from_raw(raw: {}) -> Option<Self> {{", raw_type)?;
+        writeln!(out, "        match raw {{")?;
+        for v in &m.variants {
+            writeln!(out, "            {} => Some({}::{}),", pattern(&v.1), m.name, v.0)?;
+        }
+        writeln!(out, "            _ => None,")?;
+        writeln!(out, "        }}")?;
+        writeln!(out, "    }}")?;
+    }
+    writeln!(out, "}}")?;
+    writeln!(out)?;
+
+    writeln!(out, "impl ::diesel_custom_type::CustomSqlType for {} {{", m.name)?;
+    writeln!(out, "    type DataBaseType = ::diesel::types::{};", sql_type)?;
+    writeln!(out, "    type RawType = {};", raw_type)?;
+    writeln!(out, "    type Error = ::diesel_custom_type::adapters::InvalidValueError;")?;
+    writeln!(out)?;
+    writeln!(out, "    fn to_database_type(&self) -> {} {{", raw_type)?;
+    if is_text {
+        writeln!(out, "        match *self {{")?;
+        for v in &m.variants {
+            writeln!(out, "            {}::{} => {}.into(),", m.name, v.0, literal(&v.1[0]))?;
+        }
+        writeln!(out, "        }}")?;
+    } else {
+        writeln!(out, "        self.to_raw()")?;
+    }
+    writeln!(out, "    }}")?;
+    writeln!(out)?;
+    writeln!(
+        out,
+        "    fn from_database_type(v: &{}) -> Result<Self, ::diesel_custom_type::adapters::InvalidValueError> {{",
+        raw_type
+    )?;
+    if is_text {
+        writeln!(out, "        match &**v {{")?;
+        for v in &m.variants {
+            writeln!(out, "            {} => Ok({}::{}),", pattern(&v.1), m.name, v.0)?;
+        }
+    } else {
+        writeln!(out, "        match {}::from_raw(*v) {{", m.name)?;
+        writeln!(out, "            Some(value) => Ok(value),")?;
+    }
+    writeln!(
+        out,
+        "            _ => Err(::diesel_custom_type::adapters::InvalidValueError::new({:?}, v, \"unknown value\")),",
+        m.name
+    )?;
+    writeln!(out, "        }}")?;
+    writeln!(out, "    }}")?;
+    writeln!(out, "}}")?;
+    writeln!(out)?;
+
+    writeln!(out, "impl ::diesel_custom_type::Variants for {} {{", m.name)?;
+    writeln!(out, "    fn variants() -> &'static [Self] {{")?;
+    let all = m.variants.iter().map(|v| format!("{}::{}", m.name, v.0)).collect::<Vec<_>>();
+    writeln!(out, "        &[{}]", all.join(", "))?;
+    writeln!(out, "    }}")?;
+    writeln!(out, "}}")?;
+    writeln!(out)?;
+
+    // The parser already rejects these mappings, the check keeps edits of
+    // the generated code from breaking them
+    writeln!(out, "// No value is read as two variants, and every variant is read back as itself")?;
+    writeln!(out, "const _: () = {{")?;
+    writeln!(out, "    let values = {}::ACCEPTED_RAW_VALUES;", m.name)?;
+    writeln!(out, "    let mut i = 0;")?;
+    writeln!(out, "    while i < values.len() {{")?;
+    writeln!(out, "        let mut j = 0;")?;
+    writeln!(out, "        while j < i {{")?;
+    if is_text {
+        writeln!(
+            out,
+            "            assert!(!::diesel_custom_type::__private::str_eq(values[i], values[j]), \"`{}` reads a value as two variants\");",
+            m.name
+        )?;
+    } else {
+        writeln!(out, "            assert!(values[i] != values[j], \"`{}` reads a value as two variants\");", m.name)?;
+    }
+    writeln!(out, "            j += 1;")?;
+    writeln!(out, "        }}")?;
+    writeln!(out, "        i += 1;")?;
+    writeln!(out, "    }}")?;
+    if !is_text {
+        writeln!(out, "    let variants = [{}];", all.join(", "))?;
+        writeln!(out, "    let mut i = 0;")?;
+        writeln!(out, "    while i < variants.len() {{")?;
+        writeln!(out, "        match {}::from_raw(variants[i].to_raw()) {{", m.name)?;
+        writeln!(
+            out,
+            "            Some(v) => assert!(v as isize == variants[i] as isize, \"a variant of `{}` is read back as another one\"),",
+            m.name
+        )?;
+        writeln!(out, "            None => panic!(\"a variant of `{}` can't be read back\"),", m.name)?;
+        writeln!(out, "        }}")?;
+        writeln!(out, "        i += 1;")?;
+        writeln!(out, "    }}")?;
+    }
+    writeln!(out, "}};")?;
+    writeln!(out)?;
+    writeln!(out, "register_custom_type!({});", m.name)
+}
@@ -0,0 +1,127 @@
+use std::any::{self, Any};
+use std::cell::RefCell;
+use std::error::Error;
+use std::fmt;
+use std::rc::Rc;
+
+thread_local! {
+    static CONTEXTS: RefCell<Vec<Rc<dyn Any>>> = RefCell::new(Vec::new());
+}
+
+/// Error returned by `conversion_context` if no context of the requested
+/// type is set on the current thread
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MissingContextError {
+    /// Name of the requested context type
+    pub type_name: &'static str,
+}
+
+impl fmt::Display for MissingContextError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "No conversion context of type `{}` is set", self.type_name)
+    }
+}
+
+impl Error for MissingContextError {
+    fn description(&self) -> &str {
+        "No conversion context set"
+    }
+}
+
+struct ContextGuard;
+
+impl Drop for ContextGuard {
+    fn drop(&mut self) {
+        CONTEXTS.with(|c| c.borrow_mut().pop());
+    }
+}
+
+/// Runs `f` with `ctx` available to `conversion_context` on the current
+/// thread
+///
+/// This is meant for custom types that need more than the raw value to be
+/// converted, for example ids pointing into a small reference table that is
+/// kept in memory. Wrap the `load` call with this function and look the
+/// registry up in `from_database_type`. Types that don't need a context are
+/// not affected.
+///
+/// Contexts can be nested, the innermost context of a given type wins. The
+/// context is removed again once `f` returns or panics.
+///
+/// ```
+/// # extern crate diesel;
+/// # #[macro_use] extern crate diesel_custom_type;
+/// use std::collections::HashMap;
+/// use diesel::pg::Pg;
+/// use diesel::types::{FromSql, Integer};
+/// use diesel_custom_type::{conversion_context, with_conversion_context, CustomSqlType};
+/// use diesel_custom_type::adapters::InvalidValueError;
+///
+/// #[derive(Debug, Clone, PartialEq)]
+/// struct Locale {
+///     id: i32,
+///     code: String,
+/// }
+///
+/// struct Locales(HashMap<i32, Locale>);
+///
+/// impl CustomSqlType for Locale {
+///     type DataBaseType = Integer;
+///     type RawType = i32;
+///     type Error = Box<std::error::Error + Send + Sync>;
+///
+///     fn to_database_type(&self) -> i32 {
+///         self.id
+///     }
+///
+///     fn from_database_type(v: &i32) -> Result<Self, Self::Error> {
+///         conversion_context(|locales: &Locales| {
+///             locales.0.get(v).cloned()
+///                 .ok_or_else(|| InvalidValueError::new("Locale", v, "unknown id").into())
+///         })?
+///     }
+/// }
+///
+/// register_custom_type!(Locale);
+///
+/// # fn main() {
+/// let mut locales = HashMap::new();
+/// locales.insert(1, Locale { id: 1, code: "de-DE".into() });
+///
+/// let load = |id: i32| {
+///     let bytes = id.to_be_bytes();
+///     <Locale as FromSql<Integer, Pg>>::from_sql(Some(&bytes[..]))
+/// };
+///
+/// with_conversion_context(Locales(locales), || {
+///     assert_eq!(load(1).unwrap().code, "de-DE");
+///     assert_eq!(load(2).unwrap_err().to_string(), "Invalid value `2` for Locale: unknown id");
+/// });
+///
+/// let err = load(1).unwrap_err();
+/// assert!(err.to_string().starts_with("No conversion context of type"));
+/// # }
+/// ```
+pub fn with_conversion_context<C, F, R>(ctx: C, f: F) -> R
+where
+    C: Any,
+    F: FnOnce() -> R,
+{
+    CONTEXTS.with(|c| c.borrow_mut().push(Rc::new(ctx)));
+    let _guard = ContextGuard;
+    f()
+}
+
+/// Calls `f` with the innermost context of type `C` set by
+/// `with_conversion_context` on the current thread
+pub fn conversion_context<C, F, R>(f: F) -> Result<R, MissingContextError>
+where
+    C: Any,
+    F: FnOnce(&C) -> R,
+{
+    let ctx = CONTEXTS.with(|c| c.borrow().iter().rev().find(|ctx| ctx.is::<C>()).cloned());
+    match ctx.as_ref().and_then(|ctx| ctx.downcast_ref::<C>()) {
+        Some(ctx) => Ok(f(ctx)),
+        None => Err(MissingContextError { type_name: any::type_name::<C>() }),
+    }
+}
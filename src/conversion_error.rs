@@ -0,0 +1,115 @@
+use std::error::Error;
+
+use diesel::result::Error as DieselError;
+
+/// Finds the error of type `E` that made a conversion fail inside diesel
+///
+/// Errors returned by `from_database_type` and `try_to_database_type` are
+/// passed to diesel unchanged, which stores them in
+/// `Error::DeserializationError` or `Error::SerializationError`. diesels
+/// error does not expose them through `Error::source`, so this looks into
+/// these variants and then follows the `source` chain of the stored error
+/// until it finds an `E`.
+///
+/// ```
+/// # extern crate diesel_custom_type;
+/// # extern crate diesel;
+/// use std::error::Error;
+/// use std::fmt;
+/// use diesel::result::Error::DeserializationError;
+/// use diesel_custom_type::conversion_error;
+/// use diesel_custom_type::adapters::InvalidValueError;
+///
+/// /// Error that only refers to the conversion error as its source
+/// #[derive(Debug)]
+/// struct ColumnError(Box<Error + Send + Sync>);
+///
+/// impl fmt::Display for ColumnError {
+///     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+///         f.write_str("Failed to read column")
+///     }
+/// }
+///
+/// impl Error for ColumnError {
+///     fn source(&self) -> Option<&(Error + 'static)> {
+///         Some(&*self.0)
+///     }
+/// }
+///
+/// # fn main() {
+/// // errors wrapping the conversion error are searched through `source`
+/// let cause = InvalidValueError::new("Color", 5, "unknown value");
+/// let chained = DeserializationError(Box::new(ColumnError(Box::new(cause))));
+/// assert_eq!(conversion_error::<InvalidValueError>(&chained).unwrap().value, "5");
+/// assert!(conversion_error::<fmt::Error>(&chained).is_none());
+/// # }
+/// ```
+///
+/// Finding the error of a failed query:
+///
+/// ```no_run
+/// # #[macro_use] extern crate diesel_custom_type;
+/// # #[macro_use] extern crate diesel;
+/// use diesel::prelude::*;
+/// use diesel::pg::PgConnection;
+/// use diesel::types::SmallInt;
+/// use diesel_custom_type::{conversion_error, CustomSqlType};
+/// use diesel_custom_type::adapters::InvalidValueError;
+///
+/// #[derive(Debug)]
+/// enum Color {
+///     Red = 1,
+/// }
+///
+/// impl CustomSqlType for Color {
+///     type DataBaseType = SmallInt;
+///     type RawType = i16;
+///     type Error = InvalidValueError;
+///
+///     fn to_database_type(&self) -> i16 {
+///         1
+///     }
+///
+///     fn from_database_type(v: &i16) -> Result<Self, InvalidValueError> {
+///         match *v {
+///             1 => Ok(Color::Red),
+///             v => Err(InvalidValueError::new("Color", v, "unknown value")),
+///         }
+///     }
+/// }
+///
+/// register_custom_type!(Color);
+///
+/// table! {
+///     cars {
+///         id -> Integer,
+///         color -> SmallInt,
+///     }
+/// }
+///
+/// # fn main() {
+/// let conn = PgConnection::establish(&::std::env::var("DATABASE_URL").unwrap()).unwrap();
+/// conn.execute("CREATE TEMPORARY TABLE cars (id SERIAL PRIMARY KEY, color SMALLINT NOT NULL)").unwrap();
+/// conn.execute("INSERT INTO cars (color) VALUES (1), (4)").unwrap();
+///
+/// let err = cars::table.select(cars::color).load::<Color>(&conn).unwrap_err();
+/// let cause = conversion_error::<InvalidValueError>(&err).unwrap();
+/// assert_eq!(cause.value, "4");
+/// assert_eq!(cause.type_name, "Color");
+/// # }
+/// ```
+pub fn conversion_error<E: Error + 'static>(err: &DieselError) -> Option<&E> {
+    let mut current: &(dyn Error + 'static) = match *err {
+        DieselError::DeserializationError(ref e) | DieselError::SerializationError(ref e) => &**e,
+        _ => return None,
+    };
+    loop {
+        if let Some(e) = current.downcast_ref::<E>() {
+            return Some(e);
+        }
+        if let Some(e) = current.downcast_ref::<DieselError>() {
+            return conversion_error(e);
+        }
+        current = current.source()?;
+    }
+}
@@ -0,0 +1,316 @@
+//! Query builder helpers for custom types
+
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::marker::PhantomData;
+
+use diesel::backend::Backend;
+use diesel::expression::{Expression, NonAggregate, SelectableExpression};
+use diesel::expression::SqlLiteral;
+use diesel::expression::dsl::sql;
+use diesel::query_builder::{AsQuery, BuildQueryResult, QueryFragment, QueryId};
+use diesel::result::QueryResult;
+use diesel::types::{BigInt, HasSqlType};
+use diesel::{Connection, GroupByDsl, LoadDsl, Queryable, SelectDsl};
+
+use {CustomSqlType, Variants};
+
+/// Expression returned by `as_custom`
+#[derive(Debug)]
+pub struct AsCustom<T, E> {
+    expr: E,
+    target: PhantomData<fn() -> T>,
+}
+
+impl<T, E: Clone> Clone for AsCustom<T, E> {
+    fn clone(&self) -> Self {
+        AsCustom {
+            expr: self.expr.clone(),
+            target: PhantomData,
+        }
+    }
+}
+
+impl<T, E: Copy> Copy for AsCustom<T, E> {}
+
+/// Marks an arbitrary SQL expression as producing values of the custom type
+/// `T`
+///
+/// The expression must have `T::DataBaseType` as its SQL type, anything
+/// else is rejected at compile time. The resulting expression keeps that SQL
+/// type, so it can be loaded into `T` and compared with values of `T`. This
+/// is useful for expressions diesel can't express, like `COALESCE` or
+/// `CASE`, written with `sql`.
+///
+/// ```
+/// # #[macro_use] extern crate diesel;
+/// # #[macro_use] extern crate diesel_custom_type;
+/// # use std::error::Error;
+/// use diesel::expression::dsl::sql;
+/// use diesel::prelude::*;
+/// use diesel::types::SmallInt;
+/// use diesel_custom_type::CustomSqlType;
+/// use diesel_custom_type::dsl::as_custom;
+///
+/// #[derive(Debug, Clone, Copy, PartialEq)]
+/// enum Color {
+///     Red = 1,
+///     Green = 2,
+/// }
+///
+/// impl CustomSqlType for Color {
+///     type DataBaseType = SmallInt;
+///     type RawType = i16;
+///     type Error = Box<Error + Send + Sync>;
+///
+///     fn to_database_type(&self) -> i16 {
+///         *self as i16
+///     }
+///
+///     fn from_database_type(v: &i16) -> Result<Self, Self::Error> {
+///         match *v {
+///             1 => Ok(Color::Red),
+///             2 => Ok(Color::Green),
+///             v => Err(format!("Unknown color {}", v).into()),
+///         }
+///     }
+/// }
+///
+/// register_custom_type!(Color);
+///
+/// table! {
+///     users {
+///         id -> Integer,
+///         color -> SmallInt,
+///         override_color -> Nullable<SmallInt>,
+///     }
+/// }
+///
+/// fn effective_colors(conn: &diesel::pg::PgConnection) -> QueryResult<Vec<Color>> {
+///     let effective_color = as_custom::<Color, _>(sql::<SmallInt>("COALESCE(override_color, color)"));
+///     users::table.select(effective_color).load(conn)
+/// }
+///
+/// # fn main() {
+/// let effective_color = as_custom::<Color, _>(sql::<SmallInt>("COALESCE(override_color, color)"));
+/// let query = users::table
+///     .select(effective_color.clone())
+///     .filter(effective_color.eq(Color::Green));
+/// assert_eq!(
+///     debug_sql!(query),
+///     "SELECT COALESCE(override_color, color) FROM `users` WHERE COALESCE(override_color, color) = ?"
+/// );
+/// # let _ = effective_colors;
+/// # }
+/// ```
+///
+/// Expressions of the wrong SQL type do not compile:
+///
+/// ```compile_fail
+/// # #[macro_use] extern crate diesel_custom_type;
+/// # extern crate diesel;
+/// # use std::error::Error;
+/// use diesel::expression::dsl::sql;
+/// use diesel::types::{SmallInt, Text};
+/// use diesel_custom_type::CustomSqlType;
+/// use diesel_custom_type::dsl::as_custom;
+///
+/// enum Color {
+///     Red = 1,
+/// }
+///
+/// impl CustomSqlType for Color {
+///     type DataBaseType = SmallInt;
+///     type RawType = i16;
+///     type Error = Box<Error + Send + Sync>;
+///
+///     fn to_database_type(&self) -> i16 {
+///         1
+///     }
+///
+///     fn from_database_type(_: &i16) -> Result<Self, Self::Error> {
+///         Ok(Color::Red)
+///     }
+/// }
+///
+/// # fn main() {
+/// let _ = as_custom::<Color, _>(sql::<Text>("'red'"));
+/// # }
+/// ```
+pub fn as_custom<T, E>(expr: E) -> AsCustom<T, E>
+where
+    T: CustomSqlType,
+    E: Expression<SqlType = T::DataBaseType>,
+{
+    AsCustom {
+        expr,
+        target: PhantomData,
+    }
+}
+
+impl<T, E: Expression> Expression for AsCustom<T, E> {
+    type SqlType = E::SqlType;
+}
+
+impl<T, E, DB> QueryFragment<DB> for AsCustom<T, E>
+where
+    DB: Backend,
+    E: QueryFragment<DB>,
+{
+    fn to_sql(&self, out: &mut DB::QueryBuilder) -> BuildQueryResult {
+        self.expr.to_sql(out)
+    }
+
+    fn collect_binds(&self, out: &mut DB::BindCollector) -> QueryResult<()> {
+        self.expr.collect_binds(out)
+    }
+
+    fn is_safe_to_cache_prepared(&self) -> bool {
+        self.expr.is_safe_to_cache_prepared()
+    }
+}
+
+// The wrapper does not change the generated SQL, so queries can share the
+// prepared statement cache with the unwrapped expression
+impl<T, E: QueryId> QueryId for AsCustom<T, E> {
+    type QueryId = E::QueryId;
+
+    fn has_static_query_id() -> bool {
+        E::has_static_query_id()
+    }
+}
+
+impl<T, E, QS> SelectableExpression<QS> for AsCustom<T, E> where E: SelectableExpression<QS> {}
+
+impl<T, E: NonAggregate> NonAggregate for AsCustom<T, E> {}
+
+// Types of the query built by `count_by_variant`
+type CountSqlType<C> = (<C as Expression>::SqlType, BigInt);
+type CountSelect<Q, C> = <Q as SelectDsl<(C, SqlLiteral<BigInt>), CountSqlType<C>>>::Output;
+type CountQuery<Q, C> = <CountSelect<Q, C> as GroupByDsl<C>>::Output;
+
+/// Counts the rows of `query` for every value of the custom type `T` in
+/// `column`
+///
+/// Runs `SELECT column, COUNT(*) ... GROUP BY column` and reads the values
+/// through the `FromSql` implementation of `T`, so unknown values in the
+/// column fail the whole query. Values read as the same variant, like
+/// aliases of a renamed value, are added up. Values without rows are not
+/// part of the result, use `count_all_variants` to include them with a count
+/// of 0.
+///
+/// ```no_run
+/// # #[macro_use] extern crate diesel;
+/// # #[macro_use] extern crate diesel_custom_type;
+/// # use std::error::Error;
+/// use diesel::prelude::*;
+/// use diesel::pg::PgConnection;
+/// use diesel::types::SmallInt;
+/// use diesel_custom_type::{CustomSqlType, Variants};
+/// use diesel_custom_type::dsl::{count_all_variants, count_by_variant};
+///
+/// #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// enum Status {
+///     Open = 1,
+///     Blocked = 2,
+///     Closed = 3,
+/// }
+///
+/// impl Variants for Status {
+///     fn variants() -> &'static [Self] {
+///         &[Status::Open, Status::Blocked, Status::Closed]
+///     }
+/// }
+///
+/// impl CustomSqlType for Status {
+///     type DataBaseType = SmallInt;
+///     type RawType = i16;
+///     type Error = Box<Error + Send + Sync>;
+///
+///     fn to_database_type(&self) -> i16 {
+///         *self as i16
+///     }
+///
+///     fn from_database_type(v: &i16) -> Result<Self, Self::Error> {
+///         match *v {
+///             1 => Ok(Status::Open),
+///             2 => Ok(Status::Blocked),
+///             // closed tasks used to be stored as 5
+///             3 | 5 => Ok(Status::Closed),
+///             v => Err(format!("Unknown status {}", v).into()),
+///         }
+///     }
+/// }
+///
+/// register_custom_type!(Status);
+///
+/// table! {
+///     tasks {
+///         id -> Integer,
+///         status -> SmallInt,
+///     }
+/// }
+///
+/// # fn main() {
+/// let conn = PgConnection::establish(&::std::env::var("DATABASE_URL").unwrap()).unwrap();
+/// conn.execute("CREATE TEMPORARY TABLE tasks (id SERIAL PRIMARY KEY, status SMALLINT NOT NULL)").unwrap();
+/// conn.execute("INSERT INTO tasks (status) VALUES (1), (1), (3), (1), (5)").unwrap();
+///
+/// let counts = count_by_variant::<Status, _, _, _>(tasks::table, tasks::status, &conn).unwrap();
+/// assert_eq!(counts.len(), 2);
+/// assert_eq!(counts[&Status::Open], 3);
+/// assert_eq!(counts[&Status::Closed], 2);
+///
+/// let counts = count_all_variants::<Status, _, _, _>(tasks::table.filter(tasks::id.gt(1)), tasks::status, &conn).unwrap();
+/// assert_eq!(counts[&Status::Open], 2);
+/// assert_eq!(counts[&Status::Blocked], 0);
+/// assert_eq!(counts[&Status::Closed], 2);
+///
+/// conn.execute("INSERT INTO tasks (status) VALUES (4)").unwrap();
+/// let err = count_by_variant::<Status, _, _, _>(tasks::table, tasks::status, &conn).unwrap_err();
+/// assert_eq!(err.to_string(), "Unknown status 4");
+/// # }
+/// ```
+pub fn count_by_variant<T, Q, C, Conn>(query: Q, column: C, conn: &Conn) -> QueryResult<HashMap<T, i64>>
+where
+    T: CustomSqlType<DataBaseType = C::SqlType> + Hash + Eq,
+    C: Expression + NonAggregate + Copy,
+    Conn: Connection,
+    Q: SelectDsl<(C, SqlLiteral<BigInt>), CountSqlType<C>>,
+    CountSelect<Q, C>: GroupByDsl<C>,
+    CountQuery<Q, C>: LoadDsl<Conn>,
+    Conn::Backend: HasSqlType<<CountQuery<Q, C> as AsQuery>::SqlType>,
+    (T, i64): Queryable<<CountQuery<Q, C> as AsQuery>::SqlType, Conn::Backend>,
+{
+    // diesel does not allow aggregates next to columns in a select clause,
+    // so the count is written as SQL
+    let rows = query
+        .select((column, sql::<BigInt>("COUNT(*)")))
+        .group_by(column)
+        .load::<(T, i64)>(conn)?;
+    let mut counts = HashMap::new();
+    for (v, count) in rows {
+        *counts.entry(v).or_insert(0) += count;
+    }
+    Ok(counts)
+}
+
+/// Like `count_by_variant`, but includes every value listed by `Variants`,
+/// with a count of 0 for values without rows
+pub fn count_all_variants<T, Q, C, Conn>(query: Q, column: C, conn: &Conn) -> QueryResult<HashMap<T, i64>>
+where
+    T: CustomSqlType<DataBaseType = C::SqlType> + Variants + Copy + Hash + Eq,
+    C: Expression + NonAggregate + Copy,
+    Conn: Connection,
+    Q: SelectDsl<(C, SqlLiteral<BigInt>), CountSqlType<C>>,
+    CountSelect<Q, C>: GroupByDsl<C>,
+    CountQuery<Q, C>: LoadDsl<Conn>,
+    Conn::Backend: HasSqlType<<CountQuery<Q, C> as AsQuery>::SqlType>,
+    (T, i64): Queryable<<CountQuery<Q, C> as AsQuery>::SqlType, Conn::Backend>,
+{
+    let mut counts = count_by_variant(query, column, conn)?;
+    for v in T::variants() {
+        counts.entry(*v).or_insert(0);
+    }
+    Ok(counts)
+}
@@ -24,6 +24,7 @@
 //!impl CustomSqlType for Color {
 //!    type DataBaseType = SmallInt;
 //!    type RawType = i16;
+//!    type Error = Box<Error + Send + Sync>;
 //!
 //!    fn to_database_type(&self) -> i16 {
 //!        *self as i16
@@ -82,81 +83,2041 @@
 //!# fn main(){}
 //!
 //!```
+#[macro_use]
 extern crate diesel;
 
 use std::error::Error;
+use std::fmt;
 
 /// Trait indicating how to convert a custom type into a diesel known SQL-type
+///
+/// Conversion errors keep their concrete type when `from_database_type` is
+/// called directly, they are only boxed by the generated diesel
+/// implementations.
+///
+/// ```
+/// # #[macro_use] extern crate diesel;
+/// # #[macro_use] extern crate diesel_custom_type;
+/// use std::error::Error;
+/// use std::fmt;
+/// use diesel::pg::Pg;
+/// use diesel::types::{FromSql, SmallInt};
+/// use diesel_custom_type::CustomSqlType;
+///
+/// #[derive(Debug, PartialEq)]
+/// enum Color {
+///     Red = 1,
+/// }
+///
+/// #[derive(Debug, PartialEq)]
+/// enum ColorError {
+///     Unknown(i16),
+/// }
+///
+/// impl fmt::Display for ColorError {
+///     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+///         match *self {
+///             ColorError::Unknown(v) => write!(f, "Unknown value {} for Color found", v),
+///         }
+///     }
+/// }
+///
+/// impl Error for ColorError {}
+///
+/// impl CustomSqlType for Color {
+///     type DataBaseType = SmallInt;
+///     type RawType = i16;
+///     type Error = ColorError;
+///
+///     fn to_database_type(&self) -> i16 {
+///         1
+///     }
+///
+///     fn from_database_type(v: &i16) -> Result<Self, ColorError> {
+///         match *v {
+///             1 => Ok(Color::Red),
+///             v => Err(ColorError::Unknown(v)),
+///         }
+///     }
+/// }
+///
+/// register_custom_type!(Color);
+///
+/// # fn main() {
+/// match Color::from_database_type(&4) {
+///     Err(ColorError::Unknown(v)) => assert_eq!(v, 4),
+///     _ => unreachable!(),
+/// }
+///
+/// let err = <Color as FromSql<SmallInt, Pg>>::from_sql(Some(&[0, 4])).unwrap_err();
+/// assert_eq!(err.to_string(), "Unknown value 4 for Color found");
+/// assert_eq!(<Color as FromSql<SmallInt, Pg>>::from_sql(Some(&[0, 1])).unwrap(), Color::Red);
+/// # }
+/// ```
 pub trait CustomSqlType: Sized {
     /// [Diesel type](http://docs.diesel.rs/diesel/types/index.html)
     type DataBaseType;
     /// Raw rust type corresponding to the diesel type
     type RawType;
+    /// Error returned by `from_database_type`
+    ///
+    /// Use `Box<Error + Send + Sync>` if there is no dedicated error type
+    type Error: Into<Box<dyn Error + Send + Sync>>;
 
     /// How to convert the custom type into the database type
     fn to_database_type(&self) -> Self::RawType;
 
+    /// How to convert the custom type into the database type, for types
+    /// where this conversion may fail
+    ///
+    /// This is the conversion used when writing to the database. Defaults to
+    /// `to_database_type`
+    ///
+    /// diesel 0.8 panics if a bind parameter can't be serialized, so an error
+    /// returned here is only returned by direct `to_sql` calls. In a query it
+    /// panics, validate values from untrusted input before building the query.
+    fn try_to_database_type(&self) -> Result<Self::RawType, Box<dyn Error + Send + Sync>> {
+        Ok(self.to_database_type())
+    }
+
     /// How to convert the database type into the custom type
-    fn from_database_type(&Self::RawType) -> Result<Self, Box<Error + Send + Sync>>;
+    fn from_database_type(v: &Self::RawType) -> Result<Self, Self::Error>;
+
+    /// Converts the custom type into the database type, consuming it
+    ///
+    /// Defaults to `to_database_type`, types wrapping their raw value can
+    /// override this to move it out instead of cloning it. Never used by the
+    /// generated diesel implementations.
+    fn into_raw(self) -> Self::RawType {
+        self.to_database_type()
+    }
+}
+
+/// Custom types with a conversion from raw values that skips validation
+///
+/// Meant for bulk conversions of values that were already validated, for
+/// example by a `CHECK` constraint or an earlier pass. The generated diesel
+/// implementations always use `from_database_type`, values read from the
+/// database are still checked.
+///
+/// # Safety
+///
+/// For every raw value `from_database_type` accepts, `from_raw_unchecked`
+/// must return the same value as `from_database_type`.
+///
+/// ```
+/// # #[macro_use] extern crate diesel_custom_type;
+/// # extern crate diesel;
+/// # use std::error::Error;
+/// use diesel_custom_type::{CustomSqlType, TrustedRawMapping, Variants};
+///
+/// #[derive(Debug, Clone, Copy, PartialEq)]
+/// #[repr(i16)]
+/// enum Color {
+///     Red = 1,
+///     Green = 2,
+/// }
+///
+/// impl Variants for Color {
+///     fn variants() -> &'static [Self] {
+///         &[Color::Red, Color::Green]
+///     }
+/// }
+///
+/// impl CustomSqlType for Color {
+///     type DataBaseType = diesel::types::SmallInt;
+///     type RawType = i16;
+///     type Error = Box<Error + Send + Sync>;
+///
+///     fn to_database_type(&self) -> i16 {
+///         *self as i16
+///     }
+///
+///     fn from_database_type(v: &i16) -> Result<Self, Self::Error> {
+///         match *v {
+///             1 => Ok(Color::Red),
+///             2 => Ok(Color::Green),
+///             v => Err(format!("Unknown color {}", v).into()),
+///         }
+///     }
+/// }
+///
+/// // `from_database_type` accepts exactly the discriminants of `Color`
+/// unsafe impl TrustedRawMapping for Color {
+///     unsafe fn from_raw_unchecked(raw: i16) -> Self {
+///         unsafe { std::mem::transmute::<i16, Color>(raw) }
+///     }
+/// }
+///
+/// # fn main() {
+/// for v in Color::variants() {
+///     let raw = v.into_raw();
+///     let checked = Color::from_database_type(&raw).unwrap();
+///     // `raw` was written by `into_raw`, so it is valid
+///     let unchecked = unsafe { Color::from_raw_unchecked(raw) };
+///     assert_eq!(checked, unchecked);
+/// }
+/// # }
+/// ```
+pub unsafe trait TrustedRawMapping: CustomSqlType {
+    /// Converts a raw value into the custom type without validating it
+    ///
+    /// # Safety
+    ///
+    /// `raw` must be a value `from_database_type` accepts. Passing any other
+    /// value is undefined behaviour.
+    unsafe fn from_raw_unchecked(raw: Self::RawType) -> Self;
+}
+
+/// Trait for custom types with a fixed set of values, like fieldless enums
+pub trait Variants: Sized + 'static {
+    /// All values of the type, in declaration order
+    fn variants() -> &'static [Self];
+}
+
+/// Checks that every value listed by `Variants` is written as a distinct raw
+/// value and read back as itself
+///
+/// Meant to be called from a test, so that a variant missing from
+/// `from_database_type` is noticed before it is read in production. Panics
+/// naming the first variant that doesn't round trip or that shares its raw
+/// value with another variant.
+///
+/// ```
+/// # extern crate diesel_custom_type;
+/// # use std::error::Error;
+/// # use diesel_custom_type::{assert_mapping_exhaustive, CustomSqlType, Variants};
+/// #[derive(Debug, Clone, Copy, PartialEq)]
+/// #[repr(i16)]
+/// enum Color {
+///     Red = 1,
+///     Green = 2,
+/// }
+///
+/// impl Variants for Color {
+///     fn variants() -> &'static [Self] {
+///         &[Color::Red, Color::Green]
+///     }
+/// }
+///
+/// impl CustomSqlType for Color {
+///     type DataBaseType = diesel::types::SmallInt;
+///     type RawType = i16;
+///     type Error = Box<Error + Send + Sync>;
+///
+///     fn to_database_type(&self) -> i16 {
+///         *self as i16
+///     }
+///
+///     fn from_database_type(v: &i16) -> Result<Self, Self::Error> {
+///         match *v {
+///             1 => Ok(Color::Red),
+///             2 => Ok(Color::Green),
+///             v => Err(format!("Unknown value {} for Color found", v).into()),
+///         }
+///     }
+/// }
+///
+/// # fn main() {
+/// assert_mapping_exhaustive::<Color>();
+/// # }
+/// ```
+///
+/// Forgetting to map a new variant back:
+///
+/// ```should_panic
+/// # extern crate diesel_custom_type;
+/// # use std::error::Error;
+/// # use diesel_custom_type::{assert_mapping_exhaustive, CustomSqlType, Variants};
+/// #[derive(Debug, Clone, Copy, PartialEq)]
+/// enum Color {
+///     Red = 1,
+///     Green = 2,
+///     Blue = 3,
+/// }
+///
+/// impl Variants for Color {
+///     fn variants() -> &'static [Self] {
+///         &[Color::Red, Color::Green, Color::Blue]
+///     }
+/// }
+///
+/// impl CustomSqlType for Color {
+///     type DataBaseType = diesel::types::SmallInt;
+///     type RawType = i16;
+///     type Error = Box<Error + Send + Sync>;
+///
+///     fn to_database_type(&self) -> i16 {
+///         *self as i16
+///     }
+///
+///     fn from_database_type(v: &i16) -> Result<Self, Self::Error> {
+///         match *v {
+///             1 => Ok(Color::Red),
+///             2 => Ok(Color::Green),
+///             v => Err(format!("Unknown value {} for Color found", v).into()),
+///         }
+///     }
+/// }
+///
+/// # fn main() {
+/// assert_mapping_exhaustive::<Color>();
+/// # }
+/// ```
+///
+/// Two variants stored as the same value:
+///
+/// ```should_panic
+/// # extern crate diesel_custom_type;
+/// # use std::error::Error;
+/// # use diesel_custom_type::{assert_mapping_exhaustive, CustomSqlType, Variants};
+/// #[derive(Debug, Clone, Copy, PartialEq)]
+/// enum Color {
+///     Red = 1,
+///     Green = 2,
+///     Blue = 3,
+/// }
+///
+/// impl Variants for Color {
+///     fn variants() -> &'static [Self] {
+///         &[Color::Red, Color::Green, Color::Blue]
+///     }
+/// }
+///
+/// impl CustomSqlType for Color {
+///     type DataBaseType = diesel::types::SmallInt;
+///     type RawType = i16;
+///     type Error = Box<Error + Send + Sync>;
+///
+///     fn to_database_type(&self) -> i16 {
+///         match *self {
+///             Color::Blue => 2,
+///             c => c as i16,
+///         }
+///     }
+///
+///     fn from_database_type(v: &i16) -> Result<Self, Self::Error> {
+///         match *v {
+///             1 => Ok(Color::Red),
+///             2 => Ok(Color::Green),
+///             v => Err(format!("Unknown value {} for Color found", v).into()),
+///         }
+///     }
+/// }
+///
+/// # fn main() {
+/// assert_mapping_exhaustive::<Color>();
+/// # }
+/// ```
+pub fn assert_mapping_exhaustive<T>()
+where
+    T: Variants + CustomSqlType + PartialEq + fmt::Debug,
+    T::RawType: PartialEq + fmt::Debug,
+{
+    let variants = T::variants();
+    for (i, v) in variants.iter().enumerate() {
+        let raw = v.to_database_type();
+        if let Some(other) = variants[..i].iter().find(|o| o.to_database_type() == raw) {
+            panic!("`{:?}` and `{:?}` are both stored as {:?}", other, v, raw);
+        }
+        match T::from_database_type(&raw) {
+            Ok(ref back) if back == v => {}
+            Ok(back) => panic!("`{:?}` is stored as {:?}, which is read back as `{:?}`", v, raw, back),
+            Err(e) => {
+                let e: Box<dyn Error + Send + Sync> = e.into();
+                panic!("`{:?}` is stored as {:?}, which can't be read back: {}", v, raw, e)
+            }
+        }
+    }
 }
 
 /// Macro to generate all needed trait implementations for diesel.
 /// The macro assumes that `CustomSqlType` is implemented for your target type
+///
+/// Generic types are registered by listing the generic parameters in
+/// brackets, followed by the type and the bounds needed for the
+/// `CustomSqlType` implementation:
+///
+/// ```ignore
+/// register_custom_type!(impl[T] Wrapper<T> where T: CustomSqlType);
+/// ```
+///
+/// Additional implementations are enabled by options listed after a `;`,
+/// separated by commas. Options can be combined freely and work with both
+/// forms, the base implementations are generated once:
+///
+/// ```
+/// # #[macro_use] extern crate diesel;
+/// # #[macro_use] extern crate diesel_custom_type;
+/// # use std::error::Error;
+/// use std::convert::TryFrom;
+/// use std::marker::PhantomData;
+/// use diesel::pg::Pg;
+/// use diesel::types::{FromSql, Integer, SmallInt};
+/// use diesel_custom_type::CustomSqlType;
+///
+/// #[derive(Debug, Clone, Copy)]
+/// enum Priority {
+///     Low = 1,
+///     High = 10,
+/// }
+///
+/// impl CustomSqlType for Priority {
+///     type DataBaseType = SmallInt;
+///     type RawType = i16;
+///     type Error = Box<Error + Send + Sync>;
+///
+///     fn to_database_type(&self) -> i16 {
+///         *self as i16
+///     }
+///
+///     fn from_database_type(v: &i16) -> Result<Self, Self::Error> {
+///         match *v {
+///             1 => Ok(Priority::Low),
+///             10 => Ok(Priority::High),
+///             v => Err(format!("Unknown priority {}", v).into()),
+///         }
+///     }
+/// }
+///
+/// register_custom_type!(Priority; derive_str, ord_by_raw, std_conversions,
+///                       also_read_from [Integer], fits_in_raw [Priority::Low, Priority::High]);
+///
+/// /// Priority of a queue `Q`
+/// #[derive(Debug, Clone, Copy)]
+/// struct QueuePriority<Q>(Priority, PhantomData<Q>);
+///
+/// impl<Q> CustomSqlType for QueuePriority<Q> {
+///     type DataBaseType = SmallInt;
+///     type RawType = i16;
+///     type Error = Box<Error + Send + Sync>;
+///
+///     fn to_database_type(&self) -> i16 {
+///         self.0.to_database_type()
+///     }
+///
+///     fn from_database_type(v: &i16) -> Result<Self, Self::Error> {
+///         Priority::from_database_type(v).map(|p| QueuePriority(p, PhantomData))
+///     }
+/// }
+///
+/// register_custom_type!(impl[Q] QueuePriority<Q> where; derive_str, ord_by_raw);
+///
+/// # fn main() {
+/// assert_eq!(Priority::High.to_string(), "10");
+/// assert!(Priority::High > "1".parse().unwrap());
+/// assert_eq!(i16::from(Priority::Low), 1);
+/// assert_eq!(Priority::try_from(10).unwrap(), Priority::High);
+/// assert_eq!(<Priority as FromSql<Integer, Pg>>::from_sql(Some(&[0, 0, 0, 1])).unwrap(), Priority::Low);
+///
+/// let high = QueuePriority::<()>(Priority::High, PhantomData);
+/// assert_eq!(high.to_string(), "10");
+/// assert!(high > "1".parse().unwrap());
+/// # }
+/// ```
+///
+/// `Display` and `FromStr` implementations using the database
+/// representation can be generated as well, so the text form of a value
+/// always agrees with what is stored:
+///
+/// ```
+/// # #[macro_use] extern crate diesel;
+/// # #[macro_use] extern crate diesel_custom_type;
+/// # use std::error::Error;
+/// use diesel::types::Text;
+/// use diesel_custom_type::{CustomSqlType, Variants};
+///
+/// #[derive(Debug, Clone, Copy, PartialEq)]
+/// enum Level {
+///     Info,
+///     Warn,
+/// }
+///
+/// impl Variants for Level {
+///     fn variants() -> &'static [Self] {
+///         &[Level::Info, Level::Warn]
+///     }
+/// }
+///
+/// impl CustomSqlType for Level {
+///     type DataBaseType = Text;
+///     type RawType = String;
+///     type Error = Box<Error + Send + Sync>;
+///
+///     fn to_database_type(&self) -> String {
+///         match *self {
+///             Level::Info => "info".into(),
+///             Level::Warn => "warn".into(),
+///         }
+///     }
+///
+///     fn from_database_type(v: &String) -> Result<Self, Self::Error> {
+///         match &**v {
+///             "info" => Ok(Level::Info),
+///             "warn" => Ok(Level::Warn),
+///             v => Err(format!("Unknown level {}", v).into()),
+///         }
+///     }
+/// }
+///
+/// register_custom_type!(Level; derive_str);
+///
+/// # fn main() {
+/// assert_eq!(format!("{}", Level::Warn), "warn");
+/// assert_eq!("info".parse::<Level>().unwrap(), Level::Info);
+/// assert_eq!("debug".parse::<Level>().unwrap_err().to_string(), "Unknown level debug");
+///
+/// for level in Level::variants() {
+///     assert_eq!(level.to_string(), level.to_database_type());
+///     assert_eq!(level.to_string().parse::<Level>().unwrap(), *level);
+/// }
+/// # }
+/// ```
+///
+/// With `std_conversions` the conversions are also available as `From` and
+/// `TryFrom` implementations between the type and `RawType`, for use
+/// outside of diesel. This is opt-in since it conflicts with hand written
+/// implementations of these traits:
+///
+/// ```
+/// # #[macro_use] extern crate diesel;
+/// # #[macro_use] extern crate diesel_custom_type;
+/// use std::convert::TryFrom;
+/// use diesel::types::SmallInt;
+/// use diesel_custom_type::CustomSqlType;
+/// use diesel_custom_type::adapters::InvalidValueError;
+///
+/// #[derive(Debug, Clone, Copy, PartialEq)]
+/// enum Priority {
+///     Low = 1,
+///     High = 10,
+/// }
+///
+/// impl CustomSqlType for Priority {
+///     type DataBaseType = SmallInt;
+///     type RawType = i16;
+///     type Error = InvalidValueError;
+///
+///     fn to_database_type(&self) -> i16 {
+///         *self as i16
+///     }
+///
+///     fn from_database_type(v: &i16) -> Result<Self, InvalidValueError> {
+///         match *v {
+///             1 => Ok(Priority::Low),
+///             10 => Ok(Priority::High),
+///             v => Err(InvalidValueError::new("Priority", v, "unknown priority")),
+///         }
+///     }
+/// }
+///
+/// register_custom_type!(Priority; std_conversions);
+///
+/// # fn main() {
+/// let csv_row = ["10", "1"];
+/// let parsed = csv_row.iter()
+///     .map(|v| Priority::try_from(v.parse::<i16>().unwrap()))
+///     .collect::<Result<Vec<_>, _>>()
+///     .unwrap();
+/// assert_eq!(parsed, [Priority::High, Priority::Low]);
+///
+/// assert_eq!(i16::from(Priority::High), 10);
+/// assert_eq!(i16::from(&Priority::Low), 1);
+/// let raw: i16 = Priority::High.into();
+/// assert_eq!(raw, 10);
+///
+/// let err = Priority::try_from(5).unwrap_err();
+/// assert_eq!(err.to_string(), "Invalid value `5` for Priority: unknown priority");
+/// # }
+/// ```
+///
+/// `ord_by_raw` implements `PartialEq`, `Eq`, `Hash`, `PartialOrd` and `Ord`
+/// by comparing the database representation, so sorting in memory gives the
+/// same order as `ORDER BY` on the column. This is only supported for
+/// integer and `bool` representations, since text is sorted according to
+/// the collation of the database. Use `ord_by_raw_bytes` to compare any
+/// `Ord` representation, like `String` byte-wise, anyway:
+///
+/// ```
+/// # #[macro_use] extern crate diesel;
+/// # #[macro_use] extern crate diesel_custom_type;
+/// # use std::error::Error;
+/// use diesel::types::SmallInt;
+/// use diesel_custom_type::{CustomSqlType, Variants};
+///
+/// #[derive(Debug, Clone, Copy)]
+/// enum Priority {
+///     High,
+///     Low,
+///     Medium,
+/// }
+///
+/// impl Variants for Priority {
+///     fn variants() -> &'static [Self] {
+///         &[Priority::High, Priority::Low, Priority::Medium]
+///     }
+/// }
+///
+/// impl CustomSqlType for Priority {
+///     type DataBaseType = SmallInt;
+///     type RawType = i16;
+///     type Error = Box<Error + Send + Sync>;
+///
+///     fn to_database_type(&self) -> i16 {
+///         match *self {
+///             Priority::Low => 1,
+///             Priority::Medium => 50,
+///             Priority::High => 100,
+///         }
+///     }
+///
+///     fn from_database_type(v: &i16) -> Result<Self, Self::Error> {
+///         Priority::variants().iter().cloned()
+///             .find(|p| p.to_database_type() == *v)
+///             .ok_or_else(|| format!("Unknown priority {}", v).into())
+///     }
+/// }
+///
+/// register_custom_type!(Priority; ord_by_raw);
+///
+/// # fn main() {
+/// let mut tasks = vec![Priority::Medium, Priority::High, Priority::Low, Priority::Medium];
+///
+/// // what `ORDER BY priority` returns
+/// let mut raw = tasks.iter().map(|p| p.to_database_type()).collect::<Vec<_>>();
+/// raw.sort();
+/// let sql_order = raw.iter().map(|v| Priority::from_database_type(v).unwrap()).collect::<Vec<_>>();
+///
+/// tasks.sort();
+/// assert_eq!(tasks, sql_order);
+/// assert_eq!(tasks, [Priority::Low, Priority::Medium, Priority::Medium, Priority::High]);
+/// assert!(Priority::High > Priority::Medium);
+/// # }
+/// ```
+///
+/// Text representations need `ord_by_raw_bytes`:
+///
+/// ```compile_fail
+/// # #[macro_use] extern crate diesel_custom_type;
+/// # extern crate diesel;
+/// # use std::error::Error;
+/// use diesel::types::Text;
+/// use diesel_custom_type::CustomSqlType;
+///
+/// enum Level {
+///     Info,
+/// }
+///
+/// impl CustomSqlType for Level {
+///     type DataBaseType = Text;
+///     type RawType = String;
+///     type Error = Box<Error + Send + Sync>;
+///
+///     fn to_database_type(&self) -> String {
+///         "info".into()
+///     }
+///
+///     fn from_database_type(_: &String) -> Result<Self, Self::Error> {
+///         Ok(Level::Info)
+///     }
+/// }
+///
+/// register_custom_type!(Level; ord_by_raw);
+/// # fn main() {}
+/// ```
+///
+/// `fits_in_raw` checks at compile time that the discriminants of the
+/// listed enum variants fit into `RawType`, as `as` casts silently wrap
+/// otherwise. It works like
+/// [`assert_fits_in_raw!`](macro.assert_fits_in_raw.html), but takes the raw
+/// type from the `CustomSqlType` implementation. It needs a type without
+/// generics:
+///
+/// ```compile_fail
+/// # #[macro_use] extern crate diesel_custom_type;
+/// # extern crate diesel;
+/// # use std::error::Error;
+/// use diesel::types::SmallInt;
+/// use diesel_custom_type::CustomSqlType;
+///
+/// #[derive(Clone, Copy)]
+/// enum Size {
+///     Small = 1,
+///     Huge = 40_000,
+/// }
+///
+/// impl CustomSqlType for Size {
+///     type DataBaseType = SmallInt;
+///     type RawType = i16;
+///     type Error = Box<Error + Send + Sync>;
+///
+///     fn to_database_type(&self) -> i16 {
+///         *self as i16
+///     }
+///
+///     fn from_database_type(_: &i16) -> Result<Self, Self::Error> {
+///         Ok(Size::Small)
+///     }
+/// }
+///
+/// register_custom_type!(Size; fits_in_raw [Size::Small, Size::Huge]);
+/// # fn main() {}
+/// ```
+///
+/// Integer backed types can additionally be read from wider integer columns,
+/// for example computed columns of views. Values are narrowed into
+/// `RawType` with a checked conversion, writing still only uses
+/// `DataBaseType`:
+///
+/// ```
+/// # #[macro_use] extern crate diesel;
+/// # #[macro_use] extern crate diesel_custom_type;
+/// # use std::error::Error;
+/// use diesel::pg::Pg;
+/// use diesel::types::{BigInt, FromSql, Integer, SmallInt};
+/// use diesel_custom_type::CustomSqlType;
+///
+/// #[derive(Debug, Clone, Copy, PartialEq)]
+/// enum Status {
+///     Active = 1,
+/// }
+///
+/// impl CustomSqlType for Status {
+///     type DataBaseType = SmallInt;
+///     type RawType = i16;
+///     type Error = Box<Error + Send + Sync>;
+///
+///     fn to_database_type(&self) -> i16 {
+///         *self as i16
+///     }
+///
+///     fn from_database_type(v: &i16) -> Result<Self, Self::Error> {
+///         match *v {
+///             1 => Ok(Status::Active),
+///             v => Err(format!("Unknown status {}", v).into()),
+///         }
+///     }
+/// }
+///
+/// register_custom_type!(Status; also_read_from [Integer, BigInt]);
+///
+/// # fn main() {
+/// assert_eq!(<Status as FromSql<Integer, Pg>>::from_sql(Some(&[0, 0, 0, 1])).unwrap(), Status::Active);
+/// assert_eq!(<Status as FromSql<BigInt, Pg>>::from_sql(Some(&[0, 0, 0, 0, 0, 0, 0, 1])).unwrap(), Status::Active);
+///
+/// let err = <Status as FromSql<Integer, Pg>>::from_sql(Some(&[0, 1, 0, 0])).unwrap_err();
+/// assert_eq!(err.to_string(), "Invalid value `65536` for Status: out of range for the declared column type");
+/// let err = <Status as FromSql<Integer, Pg>>::from_sql(Some(&[0, 0, 0, 2])).unwrap_err();
+/// assert_eq!(err.to_string(), "Unknown status 2");
+/// # }
+/// ```
+///
+/// By default `ToSql` is implemented for every backend. To provide a
+/// hand written `ToSql` implementation for one backend, list the backends the
+/// implementation should be generated for instead:
+///
+/// ```
+/// # #[macro_use] extern crate diesel;
+/// # #[macro_use] extern crate diesel_custom_type;
+/// # use std::error::Error;
+/// # use std::io::Write;
+/// use diesel::backend::Debug;
+/// use diesel::pg::Pg;
+/// use diesel::types::{FromSql, IsNull, SmallInt, ToSql};
+/// use diesel_custom_type::CustomSqlType;
+///
+/// #[derive(Debug, Clone, Copy, PartialEq)]
+/// enum Color {
+///     Red = 1,
+/// }
+///
+/// impl CustomSqlType for Color {
+///     type DataBaseType = SmallInt;
+///     type RawType = i16;
+///     type Error = Box<Error + Send + Sync>;
+///
+///     fn to_database_type(&self) -> i16 {
+///         *self as i16
+///     }
+///
+///     fn from_database_type(_: &i16) -> Result<Self, Self::Error> {
+///         Ok(Color::Red)
+///     }
+/// }
+///
+/// register_custom_type!(Color; to_sql for [Pg]);
+///
+/// impl ToSql<SmallInt, Debug> for Color {
+///     fn to_sql<W: Write>(&self, out: &mut W) -> Result<IsNull, Box<Error + Send + Sync>> {
+///         out.write_all(b"red")?;
+///         Ok(IsNull::No)
+///     }
+/// }
+///
+/// # fn main() {
+/// let mut bytes = Vec::new();
+/// ToSql::<SmallInt, Pg>::to_sql(&Color::Red, &mut bytes).unwrap();
+/// assert_eq!(bytes, [0, 1]);
+///
+/// let mut bytes = Vec::new();
+/// ToSql::<SmallInt, Debug>::to_sql(&Color::Red, &mut bytes).unwrap();
+/// assert_eq!(bytes, b"red");
+///
+/// assert_eq!(<Color as FromSql<SmallInt, Pg>>::from_sql(Some(&[0, 1])).unwrap(), Color::Red);
+/// # }
+/// ```
+///
+/// Registered types work with diesels `AsChangeset!`, including
+/// `Option<Option<T>>` fields for nullable columns: `None` skips the column,
+/// `Some(None)` sets it to `NULL` and `Some(Some(v))` sets it to `v`.
+///
+/// ```
+/// # #[macro_use] extern crate diesel;
+/// # #[macro_use] extern crate diesel_custom_type;
+/// # use std::error::Error;
+/// use diesel::pg::Pg;
+/// use diesel::types::{IsNull, Nullable, SmallInt, ToSql};
+/// use diesel::ExpressionMethods;
+/// use diesel_custom_type::CustomSqlType;
+///
+/// #[derive(Clone, Copy)]
+/// enum Color {
+///     Red = 1,
+/// }
+///
+/// impl CustomSqlType for Color {
+///     type DataBaseType = SmallInt;
+///     type RawType = i16;
+///     type Error = Box<Error + Send + Sync>;
+///
+///     fn to_database_type(&self) -> i16 {
+///         *self as i16
+///     }
+///
+///     fn from_database_type(_: &i16) -> Result<Self, Self::Error> {
+///         Ok(Color::Red)
+///     }
+/// }
+///
+/// register_custom_type!(Color);
+///
+/// table! {
+///     users {
+///         id -> Integer,
+///         hair_color -> Nullable<SmallInt>,
+///     }
+/// }
+///
+/// struct HairColorChange {
+///     hair_color: Option<Option<Color>>,
+/// }
+///
+/// AsChangeset! {
+///     (users)
+///     struct HairColorChange {
+///         hair_color: Option<Option<Color>>,
+///     }
+/// }
+///
+/// # fn main() {
+/// let sql = |hair_color| {
+///     let change = HairColorChange { hair_color: hair_color };
+///     let query = diesel::update(users::table).set(&change);
+///     debug_sql!(query)
+/// };
+/// assert_eq!(sql(None), "UPDATE `users` SET ");
+/// assert_eq!(sql(Some(None)), "UPDATE `users` SET `hair_color` = ?");
+/// assert_eq!(sql(Some(Some(Color::Red))), "UPDATE `users` SET `hair_color` = ?");
+///
+/// let bind = |v: Option<Color>| {
+///     let mut bytes = Vec::new();
+///     let is_null = ToSql::<Nullable<SmallInt>, Pg>::to_sql(&v, &mut bytes).unwrap();
+///     (is_null, bytes)
+/// };
+/// assert_eq!(bind(None), (IsNull::Yes, vec![]));
+/// assert_eq!(bind(Some(Color::Red)), (IsNull::No, vec![0, 1]));
+/// # }
+/// ```
+///
+/// Registered id newtypes can be used as primary and foreign keys with
+/// diesels associations. `Identifiable` requires the id type to implement
+/// `Hash` and `Eq`, which `grouped_by` uses to match children to their
+/// parents. Filtering by and joining on the typed columns needs nothing
+/// beyond the registration.
+///
+/// ```
+/// # #[macro_use] extern crate diesel;
+/// # #[macro_use] extern crate diesel_custom_type;
+/// use std::convert::Infallible;
+/// use diesel::associations::BelongsTo;
+/// use diesel::pg::PgConnection;
+/// use diesel::prelude::*;
+/// use diesel::types::BigInt;
+/// use diesel_custom_type::CustomSqlType;
+///
+/// #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// struct UserId(i64);
+///
+/// impl CustomSqlType for UserId {
+///     type DataBaseType = BigInt;
+///     type RawType = i64;
+///     type Error = Infallible;
+///
+///     fn to_database_type(&self) -> i64 {
+///         self.0
+///     }
+///
+///     fn from_database_type(v: &i64) -> Result<Self, Infallible> {
+///         Ok(UserId(*v))
+///     }
+/// }
+///
+/// #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// struct OrderId(i64);
+///
+/// impl CustomSqlType for OrderId {
+///     type DataBaseType = BigInt;
+///     type RawType = i64;
+///     type Error = Infallible;
+///
+///     fn to_database_type(&self) -> i64 {
+///         self.0
+///     }
+///
+///     fn from_database_type(v: &i64) -> Result<Self, Infallible> {
+///         Ok(OrderId(*v))
+///     }
+/// }
+///
+/// register_custom_type!(UserId);
+/// register_custom_type!(OrderId);
+///
+/// table! {
+///     users {
+///         id -> BigInt,
+///         name -> Text,
+///     }
+/// }
+///
+/// table! {
+///     orders {
+///         id -> BigInt,
+///         user_id -> BigInt,
+///         total -> Integer,
+///     }
+/// }
+///
+/// joinable!(orders -> users (user_id));
+/// select_column_workaround!(users -> orders (id, name));
+/// select_column_workaround!(orders -> users (id, user_id, total));
+///
+/// #[derive(Debug)]
+/// struct User {
+///     id: UserId,
+///     name: String,
+/// }
+///
+/// Queryable! { struct User { id: UserId, name: String, } }
+/// Identifiable! { #[table_name(users)] struct User { id: UserId, name: String, } }
+///
+/// #[derive(Debug, PartialEq)]
+/// struct Order {
+///     id: OrderId,
+///     user_id: UserId,
+///     total: i32,
+/// }
+///
+/// Queryable! { struct Order { id: OrderId, user_id: UserId, total: i32, } }
+/// Identifiable! { #[table_name(orders)] struct Order { id: OrderId, user_id: UserId, total: i32, } }
+///
+/// impl BelongsTo<User> for Order {
+///     type ForeignKeyColumn = orders::user_id;
+///
+///     fn foreign_key(&self) -> Option<&UserId> {
+///         Some(&self.user_id)
+///     }
+///
+///     fn foreign_key_column() -> orders::user_id {
+///         orders::user_id
+///     }
+/// }
+///
+/// fn orders_by_user(conn: &PgConnection) -> QueryResult<Vec<(User, Vec<Order>)>> {
+///     let users = users::table.load::<User>(conn)?;
+///     let orders = Order::belonging_to(&users).load::<Order>(conn)?.grouped_by(&users);
+///     Ok(users.into_iter().zip(orders).collect())
+/// }
+///
+/// fn orders_with_user(conn: &PgConnection, id: UserId) -> QueryResult<Vec<(User, Order)>> {
+///     users::table.inner_join(orders::table).filter(users::id.eq(id)).load(conn)
+/// }
+///
+/// # fn main() {
+/// let users = vec![
+///     User { id: UserId(1), name: "Sean".into() },
+///     User { id: UserId(2), name: "Tess".into() },
+/// ];
+/// assert_eq!(
+///     debug_sql!(Order::belonging_to(&users).select(orders::id)),
+///     "SELECT `orders`.`id` FROM `orders` WHERE `orders`.`user_id` IN (?, ?)"
+/// );
+/// assert_eq!(
+///     debug_sql!(users::table.inner_join(orders::table).select((users::name, orders::total))),
+///     "SELECT `users`.`name`, `orders`.`total` FROM `users` INNER JOIN `orders` ON `orders`.`user_id` = `users`.`id`"
+/// );
+///
+/// let orders = vec![
+///     Order { id: OrderId(10), user_id: UserId(2), total: 5 },
+///     Order { id: OrderId(11), user_id: UserId(1), total: 7 },
+///     Order { id: OrderId(12), user_id: UserId(2), total: 9 },
+/// ];
+/// let grouped = orders.grouped_by(&users);
+/// assert_eq!(grouped[0].iter().map(|o| o.id).collect::<Vec<_>>(), [OrderId(11)]);
+/// assert_eq!(grouped[1].iter().map(|o| o.id).collect::<Vec<_>>(), [OrderId(10), OrderId(12)]);
+/// # let _ = (orders_by_user, orders_with_user);
+/// # }
+/// ```
+///
+/// Types that are only ever written can be registered with
+/// `only(ToSql, AsExpression)`, which skips the `FromSql`, `FromSqlRow` and
+/// `Queryable` implementations. Together with lifetimes in the generics this
+/// allows types borrowing from their input, for example to insert values
+/// without copying them out of a parsed buffer. Lifetimes have to be listed
+/// before any other generics.
+///
+/// ```
+/// # #[macro_use] extern crate diesel;
+/// # #[macro_use] extern crate diesel_custom_type;
+/// # use std::error::Error;
+/// use diesel::types::{SmallInt, Text};
+/// use diesel_custom_type::CustomSqlType;
+///
+/// #[derive(Clone, Copy)]
+/// enum Level {
+///     Info = 1,
+///     Warn = 2,
+/// }
+///
+/// impl CustomSqlType for Level {
+///     type DataBaseType = SmallInt;
+///     type RawType = i16;
+///     type Error = Box<Error + Send + Sync>;
+///
+///     fn to_database_type(&self) -> i16 {
+///         *self as i16
+///     }
+///
+///     fn from_database_type(_: &i16) -> Result<Self, Self::Error> {
+///         Err("Level is never read".into())
+///     }
+/// }
+///
+/// register_custom_type!(Level; only(ToSql, AsExpression));
+///
+/// /// Message text, written without copying
+/// struct Message<'a>(&'a str);
+///
+/// impl<'a> CustomSqlType for Message<'a> {
+///     type DataBaseType = Text;
+///     type RawType = &'a str;
+///     type Error = Box<Error + Send + Sync>;
+///
+///     fn to_database_type(&self) -> &'a str {
+///         self.0
+///     }
+///
+///     fn from_database_type(v: &&'a str) -> Result<Self, Self::Error> {
+///         Ok(Message(*v))
+///     }
+/// }
+///
+/// register_custom_type!(only(ToSql, AsExpression) impl['a] Message<'a> where);
+///
+/// table! {
+///     log_lines {
+///         id -> Integer,
+///         level -> SmallInt,
+///         msg -> Text,
+///     }
+/// }
+///
+/// struct LogLine<'a> {
+///     level: Level,
+///     msg: Message<'a>,
+/// }
+///
+/// Insertable! {
+///     (log_lines)
+///     struct LogLine<'a> {
+///         level: Level,
+///         msg: Message<'a>,
+///     }
+/// }
+///
+/// # fn main() {
+/// let buffer = String::from("warn disk almost full\ninfo backup done");
+/// let lines = buffer.lines().map(|line| {
+///     let (level, msg) = line.split_at(line.find(' ').unwrap());
+///     let level = if level == "warn" { Level::Warn } else { Level::Info };
+///     LogLine { level: level, msg: Message(&msg[1..]) }
+/// }).collect::<Vec<_>>();
+///
+/// let query = diesel::insert(&lines).into(log_lines::table);
+/// assert_eq!(debug_sql!(query), "INSERT INTO `log_lines` (`level`, `msg`) VALUES (?, ?), (?, ?)");
+/// # }
+/// ```
+///
+/// No reading implementations are generated for such types:
+///
+/// ```compile_fail
+/// # #[macro_use] extern crate diesel_custom_type;
+/// # extern crate diesel;
+/// # use std::error::Error;
+/// use diesel::pg::Pg;
+/// use diesel::types::{FromSql, SmallInt};
+/// use diesel_custom_type::CustomSqlType;
+///
+/// enum Level {
+///     Info = 1,
+/// }
+///
+/// impl CustomSqlType for Level {
+///     type DataBaseType = SmallInt;
+///     type RawType = i16;
+///     type Error = Box<Error + Send + Sync>;
+///
+///     fn to_database_type(&self) -> i16 {
+///         1
+///     }
+///
+///     fn from_database_type(_: &i16) -> Result<Self, Self::Error> {
+///         Ok(Level::Info)
+///     }
+/// }
+///
+/// register_custom_type!(Level; only(ToSql, AsExpression));
+///
+/// # fn main() {
+/// let _ = <Level as FromSql<SmallInt, Pg>>::from_sql(Some(&[0, 1]));
+/// # }
+/// ```
 #[macro_export]
 macro_rules! register_custom_type {
     ( $Target:ty  ) => {
+        register_custom_type!(impl[] $Target where);
+    };
+
+    ( $Target:ty; $($Options:tt)+ ) => {
+        register_custom_type!(@split [$($Options)+] [] [] $Target where []);
+    };
+
+    ( only(ToSql, AsExpression) impl[$($Generics:tt)*] $Target:ty where $($Bounds:tt)* ) => {
+        register_custom_type!(impl[$($Generics)*] $Target where $($Bounds)*; only(ToSql, AsExpression));
+    };
 
+    ( impl[$($Generics:tt)*] $Target:ty where $($Bounds:tt)* ) => {
+        register_custom_type!(@bounds [$($Generics)*] $Target where [] $($Bounds)*);
+    };
 
-        impl <DB> ::diesel::types::ToSql<<$Target as CustomSqlType>::DataBaseType, DB> for $Target
-        where $Target: CustomSqlType,
-              DB: ::diesel::backend::Backend+ ::diesel::types::HasSqlType<<$Target as CustomSqlType>::DataBaseType>,
-              <$Target as CustomSqlType>::RawType: ::diesel::types::ToSql<<$Target as CustomSqlType>::DataBaseType, DB>
+    // Collects the bounds up to the `;` starting the options, if any
+    ( @bounds $Generics:tt $Target:ty where $Bounds:tt ) => {
+        register_custom_type!(@split [] [] $Generics $Target where $Bounds);
+    };
+
+    ( @bounds $Generics:tt $Target:ty where $Bounds:tt ; $($Options:tt)* ) => {
+        register_custom_type!(@split [$($Options)*] [] $Generics $Target where $Bounds);
+    };
+
+    ( @bounds $Generics:tt $Target:ty where [$($Bounds:tt)*] $Next:tt $($Rest:tt)* ) => {
+        register_custom_type!(@bounds $Generics $Target where [$($Bounds)* $Next] $($Rest)*);
+    };
+
+    // Moves leading lifetimes out of the generics, as they need to be
+    // declared before the `DB` parameter added by the impls
+    ( @split $Options:tt [$($Lifetime:lifetime,)*] [$Next:lifetime, $($Generics:tt)*] $Target:ty where $Bounds:tt ) => {
+        register_custom_type!(@split $Options [$($Lifetime,)* $Next,] [$($Generics)*] $Target where $Bounds);
+    };
+
+    ( @split $Options:tt [$($Lifetime:lifetime,)*] [$Next:lifetime] $Target:ty where $Bounds:tt ) => {
+        register_custom_type!(@split $Options [$($Lifetime,)* $Next,] [] $Target where $Bounds);
+    };
+
+    ( @split [$($Options:tt)*] $Lifetimes:tt $Generics:tt $Target:ty where $Bounds:tt ) => {
+        register_custom_type!(@options { $Lifetimes $Generics $Target where $Bounds } [all] [read] $($Options)* ,);
+    };
+
+    // Options are handled one at a time. The ones changing the base
+    // implementations are recorded in the two brackets, which list the
+    // backends to implement `ToSql` for and whether to implement reading.
+    // The base implementations are generated once all options are handled.
+    // A comma is appended to the options, so that every option is followed
+    // by one.
+    ( @options $Ctx:tt $ToSql:tt $FromSql:tt $(,)* ) => {
+        register_custom_type!(@to_sql $ToSql $Ctx);
+        register_custom_type!(@from_sql $FromSql $Ctx);
+        register_custom_type!(@as_expression $Ctx);
+    };
+
+    ( @options $Ctx:tt $ToSql:tt $FromSql:tt to_sql for [$($Backend:ty),+ $(,)*] , $($Rest:tt)* ) => {
+        register_custom_type!(@options $Ctx [$($Backend),+] $FromSql $($Rest)*);
+    };
+
+    ( @options $Ctx:tt $ToSql:tt $FromSql:tt only(ToSql, AsExpression) , $($Rest:tt)* ) => {
+        register_custom_type!(@options $Ctx $ToSql [] $($Rest)*);
+    };
+
+    ( @options $Ctx:tt $ToSql:tt $FromSql:tt derive_str , $($Rest:tt)* ) => {
+        register_custom_type!(@derive_str $Ctx);
+        register_custom_type!(@options $Ctx $ToSql $FromSql $($Rest)*);
+    };
+
+    ( @options $Ctx:tt $ToSql:tt $FromSql:tt ord_by_raw , $($Rest:tt)* ) => {
+        register_custom_type!(@ord $Ctx $crate::__private::SqlOrdered);
+        register_custom_type!(@options $Ctx $ToSql $FromSql $($Rest)*);
+    };
+
+    ( @options $Ctx:tt $ToSql:tt $FromSql:tt ord_by_raw_bytes , $($Rest:tt)* ) => {
+        register_custom_type!(@ord $Ctx ::std::cmp::Ord);
+        register_custom_type!(@options $Ctx $ToSql $FromSql $($Rest)*);
+    };
+
+    ( @options $Ctx:tt $ToSql:tt $FromSql:tt std_conversions , $($Rest:tt)* ) => {
+        register_custom_type!(@std_conversions $Ctx);
+        register_custom_type!(@options $Ctx $ToSql $FromSql $($Rest)*);
+    };
+
+    ( @options $Ctx:tt $ToSql:tt $FromSql:tt also_read_from [$($Wide:ty),+ $(,)*] , $($Rest:tt)* ) => {
+        $(
+            register_custom_type!(@read_wide $Wide; $Ctx);
+        )+
+        register_custom_type!(@options $Ctx $ToSql $FromSql $($Rest)*);
+    };
+
+    ( @options $Ctx:tt $ToSql:tt $FromSql:tt fits_in_raw [$($Variant:path),+ $(,)*] , $($Rest:tt)* ) => {
+        $(
+            register_custom_type!(@fits_in_raw $Variant; $Ctx);
+        )+
+        register_custom_type!(@options $Ctx $ToSql $FromSql $($Rest)*);
+    };
+
+    ( @to_sql [all] { [$($Lifetime:lifetime,)*] [$($Generics:tt)*] $Target:ty where [$($Bounds:tt)*] } ) => {
+        impl<$($Lifetime,)* DB, $($Generics)*> ::diesel::types::ToSql<<$Target as $crate::CustomSqlType>::DataBaseType, DB> for $Target
+        where $Target: $crate::CustomSqlType,
+              DB: ::diesel::backend::Backend+ ::diesel::types::HasSqlType<<$Target as $crate::CustomSqlType>::DataBaseType>,
+              <$Target as $crate::CustomSqlType>::RawType: ::diesel::types::ToSql<<$Target as $crate::CustomSqlType>::DataBaseType, DB>,
+              $($Bounds)*
         {
-            fn to_sql<W: ::std::io::Write>(&self, out: &mut W) -> ::std::result::Result<::diesel::types::IsNull, Box<Error + Send + Sync>>{
-                <$Target as CustomSqlType>::RawType::to_sql(&Self::to_database_type(self),out)
+            fn to_sql<W: ::std::io::Write>(&self, out: &mut W) -> ::std::result::Result<::diesel::types::IsNull, Box<dyn std::error::Error + Send + Sync>>{
+                let v = $crate::CustomSqlType::try_to_database_type(self)?;
+                ::diesel::types::ToSql::<<$Target as $crate::CustomSqlType>::DataBaseType, DB>::to_sql(&v, out)
             }
         }
+    };
 
-        impl<DB> ::diesel::types::FromSql<<$Target as CustomSqlType>::DataBaseType, DB> for $Target
-            where $Target: CustomSqlType,
-                  DB: ::diesel::backend::Backend+ ::diesel::types::HasSqlType<<$Target as CustomSqlType>::DataBaseType>,
-                  <$Target as CustomSqlType>::RawType: ::diesel::types::FromSql<<$Target as CustomSqlType>::DataBaseType, DB>
+    ( @to_sql [$($Backend:ty),+] $Ctx:tt ) => {
+        $(
+            register_custom_type!(@to_sql_for $Backend; $Ctx);
+        )+
+    };
+
+    ( @to_sql_for $Backend:ty; { [$($Lifetime:lifetime,)*] [$($Generics:tt)*] $Target:ty where [$($Bounds:tt)*] } ) => {
+        impl<$($Lifetime,)* $($Generics)*> ::diesel::types::ToSql<<$Target as $crate::CustomSqlType>::DataBaseType, $Backend> for $Target
+        where $Target: $crate::CustomSqlType,
+              <$Target as $crate::CustomSqlType>::RawType: ::diesel::types::ToSql<<$Target as $crate::CustomSqlType>::DataBaseType, $Backend>,
+              $($Bounds)*
         {
-            fn from_sql(bytes: Option<&DB::RawValue>) -> ::std::result::Result<Self, Box<Error + Send + Sync>>{
-                match <$Target as CustomSqlType>::RawType::from_sql(bytes) {
-                    Ok(a) => Self::from_database_type(&a),
-                    Err(e) => Err(e),
-                }
+            fn to_sql<W: ::std::io::Write>(&self, out: &mut W) -> ::std::result::Result<::diesel::types::IsNull, Box<dyn std::error::Error + Send + Sync>>{
+                let v = $crate::CustomSqlType::try_to_database_type(self)?;
+                ::diesel::types::ToSql::<<$Target as $crate::CustomSqlType>::DataBaseType, $Backend>::to_sql(&v, out)
             }
         }
+    };
+
+    ( @from_sql [] $Ctx:tt ) => {};
 
-        impl<DB> ::diesel::types::FromSqlRow<<$Target as CustomSqlType>::DataBaseType, DB> for $Target
-        where DB: ::diesel::backend::Backend + ::diesel::types::HasSqlType<<$Target as CustomSqlType>::DataBaseType>,
-              $Target: ::diesel::types::FromSql<<$Target as CustomSqlType>::DataBaseType, DB>
+    ( @from_sql [read] { [$($Lifetime:lifetime,)*] [$($Generics:tt)*] $Target:ty where [$($Bounds:tt)*] } ) => {
+        impl<$($Lifetime,)* DB, $($Generics)*> ::diesel::types::FromSql<<$Target as $crate::CustomSqlType>::DataBaseType, DB> for $Target
+            where $Target: $crate::CustomSqlType,
+                  DB: ::diesel::backend::Backend+ ::diesel::types::HasSqlType<<$Target as $crate::CustomSqlType>::DataBaseType>,
+                  <$Target as $crate::CustomSqlType>::RawType: ::diesel::types::FromSql<<$Target as $crate::CustomSqlType>::DataBaseType, DB>,
+                  $($Bounds)*
         {
-            fn build_from_row<R: ::diesel::row::Row<DB>>(row: &mut R) -> ::std::result::Result<Self, Box<Error + Send + Sync>> {
-                <$Target as ::diesel::types::FromSql<<$Target as CustomSqlType>::DataBaseType, DB>>::from_sql(row.take())
+            fn from_sql(bytes: Option<&DB::RawValue>) -> ::std::result::Result<Self, Box<dyn std::error::Error + Send + Sync>>{
+                $crate::__private::from_raw(<<$Target as $crate::CustomSqlType>::RawType as ::diesel::types::FromSql<<$Target as $crate::CustomSqlType>::DataBaseType, DB>>::from_sql(bytes))
+            }
+        }
+
+        impl<$($Lifetime,)* DB, $($Generics)*> ::diesel::types::FromSqlRow<<$Target as $crate::CustomSqlType>::DataBaseType, DB> for $Target
+        where $Target: $crate::CustomSqlType,
+              DB: ::diesel::backend::Backend + ::diesel::types::HasSqlType<<$Target as $crate::CustomSqlType>::DataBaseType>,
+              $Target: ::diesel::types::FromSql<<$Target as $crate::CustomSqlType>::DataBaseType, DB>,
+              $($Bounds)*
+        {
+            fn build_from_row<R: ::diesel::row::Row<DB>>(row: &mut R) -> ::std::result::Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+                <$Target as ::diesel::types::FromSql<<$Target as $crate::CustomSqlType>::DataBaseType, DB>>::from_sql(row.take())
             }
         }
 
 
-        impl ::diesel::expression::AsExpression<<$Target as CustomSqlType>::DataBaseType> for $Target {
-            type Expression = ::diesel::expression::bound::Bound<<$Target as CustomSqlType>::DataBaseType, $Target>;
+        impl<$($Lifetime,)* DB, $($Generics)*> ::diesel::Queryable<<$Target as $crate::CustomSqlType>::DataBaseType, DB> for $Target
+        where $Target: $crate::CustomSqlType,
+              DB: ::diesel::backend::Backend + ::diesel::types::HasSqlType<<$Target as $crate::CustomSqlType>::DataBaseType>,
+              $Target: ::diesel::types::FromSqlRow<<$Target as $crate::CustomSqlType>::DataBaseType, DB>,
+              $($Bounds)*
+        {
+            type Row = Self;
+
+            fn build(row: Self) -> Self {
+                row
+            }
+        }
+    };
+
+    ( @as_expression { [$($Lifetime:lifetime,)*] [$($Generics:tt)*] $Target:ty where [$($Bounds:tt)*] } ) => {
+        impl<$($Lifetime,)* $($Generics)*> ::diesel::expression::AsExpression<<$Target as $crate::CustomSqlType>::DataBaseType> for $Target
+        where $Target: $crate::CustomSqlType,
+              $($Bounds)*
+        {
+            type Expression = ::diesel::expression::bound::Bound<<$Target as $crate::CustomSqlType>::DataBaseType, $Target>;
 
             fn as_expression(self) -> Self::Expression {
                 ::diesel::expression::bound::Bound::new(self)
             }
         }
 
-        impl<'a> ::diesel::expression::AsExpression<<$Target as CustomSqlType>::DataBaseType> for &'a $Target {
-            type Expression = ::diesel::expression::bound::Bound<<$Target as CustomSqlType>::DataBaseType, &'a $Target>;
+        impl<'__expr, $($Lifetime,)* $($Generics)*> ::diesel::expression::AsExpression<<$Target as $crate::CustomSqlType>::DataBaseType> for &'__expr $Target
+        where $Target: $crate::CustomSqlType,
+              $($Bounds)*
+        {
+            type Expression = ::diesel::expression::bound::Bound<<$Target as $crate::CustomSqlType>::DataBaseType, &'__expr $Target>;
 
             fn as_expression(self) -> Self::Expression {
                 ::diesel::expression::bound::Bound::new(self)
             }
         }
+    };
 
+    ( @derive_str { [$($Lifetime:lifetime,)*] [$($Generics:tt)*] $Target:ty where [$($Bounds:tt)*] } ) => {
+        impl<$($Lifetime,)* $($Generics)*> ::std::fmt::Display for $Target
+        where $Target: $crate::CustomSqlType,
+              <$Target as $crate::CustomSqlType>::RawType: ::std::fmt::Display,
+              $($Bounds)*
+        {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+                ::std::fmt::Display::fmt(&$crate::CustomSqlType::to_database_type(self), f)
+            }
+        }
+
+        impl<$($Lifetime,)* $($Generics)*> ::std::str::FromStr for $Target
+        where $Target: $crate::CustomSqlType,
+              <$Target as $crate::CustomSqlType>::RawType: ::std::str::FromStr,
+              <<$Target as $crate::CustomSqlType>::RawType as ::std::str::FromStr>::Err: Into<Box<dyn std::error::Error + Send + Sync>>,
+              $($Bounds)*
+        {
+            type Err = Box<dyn std::error::Error + Send + Sync>;
+
+            fn from_str(s: &str) -> ::std::result::Result<Self, Self::Err> {
+                match s.parse::<<$Target as $crate::CustomSqlType>::RawType>() {
+                    Ok(v) => <$Target as $crate::CustomSqlType>::from_database_type(&v).map_err(Into::into),
+                    Err(e) => Err(e.into()),
+                }
+            }
+        }
+    };
+
+    ( @ord { [$($Lifetime:lifetime,)*] [$($Generics:tt)*] $Target:ty where [$($Bounds:tt)*] } $Bound:path ) => {
+        impl<$($Lifetime,)* $($Generics)*> ::std::cmp::PartialEq for $Target
+        where $Target: $crate::CustomSqlType,
+              <$Target as $crate::CustomSqlType>::RawType: $Bound,
+              $($Bounds)*
+        {
+            fn eq(&self, other: &Self) -> bool {
+                $crate::CustomSqlType::to_database_type(self) == $crate::CustomSqlType::to_database_type(other)
+            }
+        }
+
+        impl<$($Lifetime,)* $($Generics)*> ::std::cmp::Eq for $Target
+        where $Target: $crate::CustomSqlType,
+              <$Target as $crate::CustomSqlType>::RawType: $Bound,
+              $($Bounds)*
+        {
+        }
+
+        impl<$($Lifetime,)* $($Generics)*> ::std::hash::Hash for $Target
+        where $Target: $crate::CustomSqlType,
+              <$Target as $crate::CustomSqlType>::RawType: $Bound + ::std::hash::Hash,
+              $($Bounds)*
+        {
+            fn hash<H: ::std::hash::Hasher>(&self, state: &mut H) {
+                ::std::hash::Hash::hash(&$crate::CustomSqlType::to_database_type(self), state)
+            }
+        }
+
+        impl<$($Lifetime,)* $($Generics)*> ::std::cmp::PartialOrd for $Target
+        where $Target: $crate::CustomSqlType,
+              <$Target as $crate::CustomSqlType>::RawType: $Bound,
+              $($Bounds)*
+        {
+            fn partial_cmp(&self, other: &Self) -> Option<::std::cmp::Ordering> {
+                Some(::std::cmp::Ord::cmp(self, other))
+            }
+        }
+
+        impl<$($Lifetime,)* $($Generics)*> ::std::cmp::Ord for $Target
+        where $Target: $crate::CustomSqlType,
+              <$Target as $crate::CustomSqlType>::RawType: $Bound,
+              $($Bounds)*
+        {
+            fn cmp(&self, other: &Self) -> ::std::cmp::Ordering {
+                ::std::cmp::Ord::cmp(
+                    &$crate::CustomSqlType::to_database_type(self),
+                    &$crate::CustomSqlType::to_database_type(other),
+                )
+            }
+        }
+    };
+
+    ( @std_conversions { [$($Lifetime:lifetime,)*] [$($Generics:tt)*] $Target:ty where [$($Bounds:tt)*] } ) => {
+        impl<'__conv, $($Lifetime,)* $($Generics)*> ::std::convert::From<&'__conv $Target> for <$Target as $crate::CustomSqlType>::RawType
+        where $Target: $crate::CustomSqlType,
+              $($Bounds)*
+        {
+            fn from(v: &'__conv $Target) -> Self {
+                $crate::CustomSqlType::to_database_type(v)
+            }
+        }
+
+        impl<$($Lifetime,)* $($Generics)*> ::std::convert::From<$Target> for <$Target as $crate::CustomSqlType>::RawType
+        where $Target: $crate::CustomSqlType,
+              $($Bounds)*
+        {
+            fn from(v: $Target) -> Self {
+                $crate::CustomSqlType::to_database_type(&v)
+            }
+        }
+
+        impl<$($Lifetime,)* $($Generics)*> ::std::convert::TryFrom<<$Target as $crate::CustomSqlType>::RawType> for $Target
+        where $Target: $crate::CustomSqlType,
+              $($Bounds)*
+        {
+            type Error = <$Target as $crate::CustomSqlType>::Error;
+
+            fn try_from(v: <$Target as $crate::CustomSqlType>::RawType) -> ::std::result::Result<Self, Self::Error> {
+                <$Target as $crate::CustomSqlType>::from_database_type(&v)
+            }
+        }
+    };
+
+    ( @read_wide $Wide:ty; { [$($Lifetime:lifetime,)*] [$($Generics:tt)*] $Target:ty where [$($Bounds:tt)*] } ) => {
+        impl<$($Lifetime,)* DB, $($Generics)*> ::diesel::types::FromSql<$Wide, DB> for $Target
+        where $Target: $crate::CustomSqlType,
+              DB: ::diesel::backend::Backend + ::diesel::types::HasSqlType<$Wide>,
+              <$Wide as $crate::__private::IntegerSqlType>::Raw: ::diesel::types::FromSql<$Wide, DB>,
+              $($Bounds)*
+        {
+            fn from_sql(bytes: Option<&DB::RawValue>) -> ::std::result::Result<Self, Box<dyn std::error::Error + Send + Sync>>{
+                $crate::__private::from_wider_raw(
+                    <<$Wide as $crate::__private::IntegerSqlType>::Raw as ::diesel::types::FromSql<$Wide, DB>>::from_sql(bytes),
+                    stringify!($Target),
+                )
+            }
+        }
+
+        impl<$($Lifetime,)* DB, $($Generics)*> ::diesel::types::FromSqlRow<$Wide, DB> for $Target
+        where DB: ::diesel::backend::Backend + ::diesel::types::HasSqlType<$Wide>,
+              $Target: ::diesel::types::FromSql<$Wide, DB>,
+              $($Bounds)*
+        {
+            fn build_from_row<R: ::diesel::row::Row<DB>>(row: &mut R) -> ::std::result::Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+                <$Target as ::diesel::types::FromSql<$Wide, DB>>::from_sql(row.take())
+            }
+        }
+
+        impl<$($Lifetime,)* DB, $($Generics)*> ::diesel::Queryable<$Wide, DB> for $Target
+        where DB: ::diesel::backend::Backend + ::diesel::types::HasSqlType<$Wide>,
+              $Target: ::diesel::types::FromSqlRow<$Wide, DB>,
+              $($Bounds)*
+        {
+            type Row = Self;
+
+            fn build(row: Self) -> Self {
+                row
+            }
+        }
+    };
+
+    ( @fits_in_raw $Variant:path; { $Lifetimes:tt $Generics:tt $Target:ty where $Bounds:tt } ) => {
+        const _: () = assert!(
+            ($Variant as i128) >= (<<$Target as $crate::CustomSqlType>::RawType>::MIN as i128)
+                && ($Variant as i128) <= (<<$Target as $crate::CustomSqlType>::RawType>::MAX as i128),
+            concat!("discriminant of `", stringify!($Variant), "` does not fit into the raw type of `", stringify!($Target), "`")
+        );
     };
 }
 
+/// Macro to register an existing type under an additional database
+/// representation
+///
+/// Generates a transparent wrapper around the type, implements
+/// `CustomSqlType` for it using the given conversion functions and registers
+/// it with `register_custom_type!`. This allows the same type to be stored in
+/// columns of different SQL types, for example while migrating a column.
+/// `to` is called with a reference to the wrapped value, `from` with a
+/// reference to the raw value and may return any error convertible into
+/// `Box<Error + Send + Sync>`.
+///
+/// ```
+/// # #[macro_use] extern crate diesel;
+/// # #[macro_use] extern crate diesel_custom_type;
+/// use diesel::pg::Pg;
+/// use diesel::types::{FromSql, SmallInt, Text, ToSql};
+///
+/// #[derive(Debug, Clone, Copy, PartialEq)]
+/// enum Status {
+///     Active,
+///     Blocked,
+/// }
+///
+/// impl Status {
+///     fn to_code(&self) -> i16 {
+///         match *self {
+///             Status::Active => 1,
+///             Status::Blocked => 2,
+///         }
+///     }
+///
+///     fn from_code(v: &i16) -> Result<Self, String> {
+///         match *v {
+///             1 => Ok(Status::Active),
+///             2 => Ok(Status::Blocked),
+///             v => Err(format!("Unknown status {}", v)),
+///         }
+///     }
+///
+///     fn to_name(&self) -> String {
+///         format!("{:?}", self).to_lowercase()
+///     }
+///
+///     fn from_name(v: &String) -> Result<Self, String> {
+///         match &**v {
+///             "active" => Ok(Status::Active),
+///             "blocked" => Ok(Status::Blocked),
+///             v => Err(format!("Unknown status {}", v)),
+///         }
+///     }
+/// }
+///
+/// register_custom_type_as! {
+///     #[derive(Debug, Clone, Copy, PartialEq)]
+///     struct StatusInt(Status): SmallInt = i16 {
+///         to = Status::to_code,
+///         from = Status::from_code,
+///     }
+/// }
+///
+/// register_custom_type_as! {
+///     #[derive(Debug, Clone, Copy, PartialEq)]
+///     struct StatusText(Status): Text = String {
+///         to = Status::to_name,
+///         from = Status::from_name,
+///     }
+/// }
+///
+/// table! {
+///     accounts {
+///         id -> Integer,
+///         old_status -> SmallInt,
+///         new_status -> Text,
+///     }
+/// }
+///
+/// struct Account {
+///     old_status: StatusInt,
+///     new_status: StatusText,
+/// }
+///
+/// Queryable! {
+///     struct Account {
+///         old_status: StatusInt,
+///         new_status: StatusText,
+///     }
+/// }
+///
+/// # fn main() {
+/// let old = <StatusInt as FromSql<SmallInt, Pg>>::from_sql(Some(&[0, 2])).unwrap();
+/// let new = <StatusText as FromSql<Text, Pg>>::from_sql(Some(b"blocked")).unwrap();
+/// assert_eq!(old.0, Status::Blocked);
+/// assert_eq!(*old, *new);
+///
+/// let mut bytes = Vec::new();
+/// ToSql::<Text, Pg>::to_sql(&StatusText(Status::Active), &mut bytes).unwrap();
+/// assert_eq!(bytes, b"active");
+///
+/// let err = <StatusText as FromSql<Text, Pg>>::from_sql(Some(b"deleted")).unwrap_err();
+/// assert_eq!(err.to_string(), "Unknown status deleted");
+/// # }
+/// ```
+#[macro_export]
+macro_rules! register_custom_type_as {
+    (
+        $(#[$attr:meta])*
+        $vis:vis struct $Name:ident($Inner:ty): $DataBaseType:ty = $RawType:ty {
+            to = $to:path,
+            from = $from:path $(,)*
+        }
+    ) => {
+        $(#[$attr])*
+        $vis struct $Name(pub $Inner);
+
+        impl $crate::CustomSqlType for $Name {
+            type DataBaseType = $DataBaseType;
+            type RawType = $RawType;
+            type Error = Box<dyn std::error::Error + Send + Sync>;
+
+            fn to_database_type(&self) -> $RawType {
+                $to(&self.0)
+            }
+
+            fn from_database_type(v: &$RawType) -> ::std::result::Result<Self, Self::Error> {
+                $from(v).map($Name).map_err(Into::into)
+            }
+        }
+
+        impl ::std::ops::Deref for $Name {
+            type Target = $Inner;
+
+            fn deref(&self) -> &$Inner {
+                &self.0
+            }
+        }
+
+        impl ::std::convert::From<$Inner> for $Name {
+            fn from(v: $Inner) -> Self {
+                $Name(v)
+            }
+        }
+
+        impl ::std::convert::From<$Name> for $Inner {
+            fn from(v: $Name) -> Self {
+                v.0
+            }
+        }
+
+        register_custom_type!($Name);
+    };
+}
+
+/// Asserts at compile time that the discriminants of fieldless enum variants
+/// fit into the raw type used by the `CustomSqlType` implementation
+///
+/// Casting a discriminant with `as` silently wraps if it is out of range for
+/// `RawType`. Place this next to the implementation to turn that into a
+/// compile error naming the offending variant. Registered types can use the
+/// `fits_in_raw` option of `register_custom_type!` instead, which doesn't
+/// need the raw type to be repeated. Types generated by `codegen` don't use
+/// casts, their values are checked while generating the code.
+///
+/// ```
+/// # #[macro_use] extern crate diesel_custom_type;
+/// enum Color {
+///     Red = 1,
+///     Green = 2,
+/// }
+///
+/// assert_fits_in_raw!(i16; Color::Red, Color::Green);
+/// # fn main() {}
+/// ```
+///
+/// ```compile_fail
+/// # #[macro_use] extern crate diesel_custom_type;
+/// enum Size {
+///     Small = 1,
+///     Huge = 40_000,
+/// }
+///
+/// assert_fits_in_raw!(i16; Size::Small, Size::Huge);
+/// # fn main() {}
+/// ```
+#[macro_export]
+macro_rules! assert_fits_in_raw {
+    ( $Raw:ty; $($Variant:path),+ $(,)* ) => {
+        $(
+            const _: () = assert!(
+                ($Variant as i128) >= (<$Raw>::MIN as i128) && ($Variant as i128) <= (<$Raw>::MAX as i128),
+                concat!("discriminant of `", stringify!($Variant), "` does not fit into `", stringify!($Raw), "`")
+            );
+        )+
+    };
+}
+
+/// Macro to generate query scopes filtering a column by a custom type
+///
+/// Generates an extension trait with a method filtering by any value of the
+/// type and one shortcut per listed value. The trait is implemented for
+/// every query the column can be filtered on, including filtered and boxed
+/// queries, so the scopes compose with further `filter` calls.
+///
+/// ```
+/// # #[macro_use] extern crate diesel;
+/// # #[macro_use] extern crate diesel_custom_type;
+/// # use std::error::Error;
+/// use diesel::prelude::*;
+/// use diesel::pg::Pg;
+/// use diesel::types::SmallInt;
+/// use diesel_custom_type::CustomSqlType;
+///
+/// #[derive(Debug, Clone, Copy, PartialEq)]
+/// enum Status {
+///     Open = 1,
+///     Closed = 2,
+/// }
+///
+/// impl CustomSqlType for Status {
+///     type DataBaseType = SmallInt;
+///     type RawType = i16;
+///     type Error = Box<Error + Send + Sync>;
+///
+///     fn to_database_type(&self) -> i16 {
+///         *self as i16
+///     }
+///
+///     fn from_database_type(v: &i16) -> Result<Self, Self::Error> {
+///         match *v {
+///             1 => Ok(Status::Open),
+///             2 => Ok(Status::Closed),
+///             v => Err(format!("Unknown status {}", v).into()),
+///         }
+///     }
+/// }
+///
+/// register_custom_type!(Status);
+///
+/// table! {
+///     tasks {
+///         id -> Integer,
+///         status -> SmallInt,
+///     }
+/// }
+///
+/// custom_type_scopes! {
+///     /// Filters for `tasks.status`
+///     pub trait TaskStatusScopes: tasks::status => Status {
+///         fn with_status;
+///         fn open => Status::Open;
+///         fn closed => Status::Closed;
+///     }
+/// }
+///
+/// # fn main() {
+/// let query = tasks::table.select(tasks::id).open().filter(tasks::id.gt(10));
+/// assert_eq!(debug_sql!(query), "SELECT `tasks`.`id` FROM `tasks` \
+///                                WHERE `tasks`.`status` = ? AND `tasks`.`id` > ?");
+///
+/// let query = tasks::table.select(tasks::id).filter(tasks::id.gt(10)).with_status(Status::Closed);
+/// assert_eq!(debug_sql!(query), "SELECT `tasks`.`id` FROM `tasks` \
+///                                WHERE `tasks`.`id` > ? AND `tasks`.`status` = ?");
+///
+/// let _boxed: tasks::BoxedQuery<Pg> = tasks::table.open().into_boxed();
+/// let _boxed: tasks::BoxedQuery<Pg> = tasks::table.into_boxed().closed().filter(tasks::id.eq(1));
+/// # }
+/// ```
+#[macro_export]
+macro_rules! custom_type_scopes {
+    (
+        $(#[$attr:meta])*
+        $vis:vis trait $Trait:ident: $column:path => $Target:ty {
+            fn $with:ident;
+            $(fn $scope:ident => $value:expr;)*
+        }
+    ) => {
+        $(#[$attr])*
+        $vis trait $Trait: Sized + ::diesel::FilterDsl<::diesel::expression::helper_types::Eq<$column, $Target>> {
+            /// Filters for rows with the given value
+            fn $with(self, v: $Target) -> ::diesel::helper_types::FindBy<Self, $column, $Target> {
+                ::diesel::FilterDsl::filter(self, ::diesel::ExpressionMethods::eq($column, v))
+            }
+
+            $(
+                #[doc = concat!("Filters for rows with the value `", stringify!($value), "`")]
+                fn $scope(self) -> ::diesel::helper_types::FindBy<Self, $column, $Target> {
+                    self.$with($value)
+                }
+            )*
+        }
+
+        impl<Q> $Trait for Q
+        where Q: ::diesel::FilterDsl<::diesel::expression::helper_types::Eq<$column, $Target>>,
+        {
+        }
+    };
+}
+
+/// Macro to generate SQL predicates comparing an expression with values of a
+/// custom type
+///
+/// Generates an extension trait with one method per listed value, for every
+/// expression with the SQL type of the custom type or the nullable version
+/// of it. `expr.is_open()` is the same as `expr.eq(Status::Open)` and can be
+/// used anywhere a boolean expression is accepted. The comparison uses SQL
+/// equality, so it is `NULL` for `NULL` values and such rows are not matched
+/// by a filter. Use this macro at most once per custom type.
+///
+/// ```
+/// # #[macro_use] extern crate diesel;
+/// # #[macro_use] extern crate diesel_custom_type;
+/// # use std::error::Error;
+/// use diesel::prelude::*;
+/// use diesel::types::SmallInt;
+/// use diesel_custom_type::CustomSqlType;
+///
+/// #[derive(Debug, Clone, Copy, PartialEq)]
+/// enum Status {
+///     Open = 1,
+///     Closed = 2,
+/// }
+///
+/// impl CustomSqlType for Status {
+///     type DataBaseType = SmallInt;
+///     type RawType = i16;
+///     type Error = Box<Error + Send + Sync>;
+///
+///     fn to_database_type(&self) -> i16 {
+///         *self as i16
+///     }
+///
+///     fn from_database_type(v: &i16) -> Result<Self, Self::Error> {
+///         match *v {
+///             1 => Ok(Status::Open),
+///             2 => Ok(Status::Closed),
+///             v => Err(format!("Unknown status {}", v).into()),
+///         }
+///     }
+/// }
+///
+/// register_custom_type!(Status);
+///
+/// custom_type_predicates! {
+///     pub trait StatusPredicates for Status {
+///         fn is_open => Status::Open;
+///         fn is_closed => Status::Closed;
+///     }
+/// }
+///
+/// table! {
+///     tasks {
+///         id -> Integer,
+///         status -> SmallInt,
+///         review_status -> Nullable<SmallInt>,
+///     }
+/// }
+///
+/// # fn main() {
+/// let query = tasks::table
+///     .select((tasks::id, tasks::status.is_closed()))
+///     .filter(tasks::status.is_open().or(tasks::review_status.is_open()));
+/// assert_eq!(
+///     debug_sql!(query),
+///     "SELECT `tasks`.`id`, `tasks`.`status` = ? FROM `tasks` \
+///      WHERE (`tasks`.`status` = ? OR `tasks`.`review_status` = ?)"
+/// );
+/// # }
+/// ```
+///
+/// Expressions of other SQL types don't get the methods:
+///
+/// ```compile_fail
+/// # #[macro_use] extern crate diesel;
+/// # #[macro_use] extern crate diesel_custom_type;
+/// # use std::error::Error;
+/// use diesel::types::SmallInt;
+/// use diesel_custom_type::CustomSqlType;
+///
+/// #[derive(Clone, Copy)]
+/// enum Status {
+///     Open = 1,
+/// }
+///
+/// impl CustomSqlType for Status {
+///     type DataBaseType = SmallInt;
+///     type RawType = i16;
+///     type Error = Box<Error + Send + Sync>;
+///
+///     fn to_database_type(&self) -> i16 {
+///         1
+///     }
+///
+///     fn from_database_type(_: &i16) -> Result<Self, Self::Error> {
+///         Ok(Status::Open)
+///     }
+/// }
+///
+/// register_custom_type!(Status);
+///
+/// custom_type_predicates! {
+///     trait StatusPredicates for Status {
+///         fn is_open => Status::Open;
+///     }
+/// }
+///
+/// table! {
+///     tasks {
+///         id -> Integer,
+///     }
+/// }
+///
+/// # fn main() {
+/// let _ = tasks::id.is_open();
+/// # }
+/// ```
+#[macro_export]
+macro_rules! custom_type_predicates {
+    (
+        $(#[$attr:meta])*
+        $vis:vis trait $Trait:ident for $Target:ty {
+            $(fn $predicate:ident => $value:expr;)+
+        }
+    ) => {
+        impl $crate::__private::CustomTypeSql<$Target> for <$Target as $crate::CustomSqlType>::DataBaseType {
+            type Value = $Target;
+
+            fn value(v: $Target) -> $Target {
+                v
+            }
+        }
+
+        impl $crate::__private::CustomTypeSql<$Target> for ::diesel::types::Nullable<<$Target as $crate::CustomSqlType>::DataBaseType> {
+            type Value = Option<$Target>;
+
+            fn value(v: $Target) -> Option<$Target> {
+                Some(v)
+            }
+        }
+
+        $(#[$attr])*
+        $vis trait $Trait: ::diesel::Expression + Sized
+        where <Self as ::diesel::Expression>::SqlType: $crate::__private::CustomTypeSql<$Target>,
+        {
+            $(
+                #[doc = concat!("Compares the expression with `", stringify!($value), "`")]
+                fn $predicate(self) -> ::diesel::expression::helper_types::Eq<
+                    Self,
+                    <<Self as ::diesel::Expression>::SqlType as $crate::__private::CustomTypeSql<$Target>>::Value,
+                > {
+                    let value = <<Self as ::diesel::Expression>::SqlType as $crate::__private::CustomTypeSql<$Target>>::value($value);
+                    ::diesel::ExpressionMethods::eq(self, value)
+                }
+            )+
+        }
+
+        impl<E> $Trait for E
+        where E: ::diesel::Expression,
+              <E as ::diesel::Expression>::SqlType: $crate::__private::CustomTypeSql<$Target>,
+        {
+        }
+    };
+}
+
+// Conversion and error handling used by the code generated by
+// `register_custom_type!`. This only depends on the custom type and not on
+// the backend, so it is instantiated once per type instead of once per type
+// and backend.
+#[doc(hidden)]
+pub mod __private {
+    use std::convert::TryFrom;
+    use std::error::Error;
+    use std::fmt;
+
+    use diesel::types::{BigInt, Integer, SmallInt};
+
+    use adapters::InvalidValueError;
+    use CustomSqlType;
+
+    #[inline(never)]
+    pub fn from_raw<T: CustomSqlType>(raw: Result<T::RawType, Box<dyn Error + Send + Sync>>) -> Result<T, Box<dyn Error + Send + Sync>> {
+        match raw {
+            Ok(a) => T::from_database_type(&a).map_err(Into::into),
+            Err(e) => Err(e),
+        }
+    }
+
+    #[inline(never)]
+    pub fn from_wider_raw<T, W>(wide: Result<W, Box<dyn Error + Send + Sync>>, type_name: &'static str) -> Result<T, Box<dyn Error + Send + Sync>>
+    where
+        T: CustomSqlType,
+        T::RawType: TryFrom<W>,
+        W: Copy + fmt::Display,
+    {
+        let wide = wide?;
+        match T::RawType::try_from(wide) {
+            Ok(a) => T::from_database_type(&a).map_err(Into::into),
+            Err(_) => Err(Box::new(InvalidValueError::new(type_name, wide, "out of range for the declared column type"))),
+        }
+    }
+
+    /// SQL types values of the custom type `T` can be compared with, used by
+    /// `custom_type_predicates!`
+    pub trait CustomTypeSql<T>: Sized {
+        type Value: ::diesel::expression::AsExpression<Self>;
+
+        fn value(v: T) -> Self::Value;
+    }
+
+    /// Raw types the database orders the same way as `Ord`
+    #[diagnostic::on_unimplemented(
+        message = "the database may order `{Self}` differently than Rust",
+        note = "use `ord_by_raw_bytes` to compare the raw values with `Ord` anyway"
+    )]
+    pub trait SqlOrdered: Ord {}
+
+    impl SqlOrdered for bool {}
+    impl SqlOrdered for i16 {}
+    impl SqlOrdered for i32 {}
+    impl SqlOrdered for i64 {}
+
+    /// `str` comparison usable in constants, used by the code generated by
+    /// `codegen`
+    pub const fn str_eq(a: &str, b: &str) -> bool {
+        let (a, b) = (a.as_bytes(), b.as_bytes());
+        if a.len() != b.len() {
+            return false;
+        }
+        let mut i = 0;
+        while i < a.len() {
+            if a[i] != b[i] {
+                return false;
+            }
+            i += 1;
+        }
+        true
+    }
+
+    /// Rust type used to read the integer SQL types
+    pub trait IntegerSqlType {
+        type Raw;
+    }
+
+    impl IntegerSqlType for SmallInt {
+        type Raw = i16;
+    }
+
+    impl IntegerSqlType for Integer {
+        type Raw = i32;
+    }
+
+    impl IntegerSqlType for BigInt {
+        type Raw = i64;
+    }
+}
+
+mod context;
+mod conversion_error;
+
+pub use context::{conversion_context, with_conversion_context, MissingContextError};
+pub use conversion_error::conversion_error;
 
+pub mod adapters;
+pub mod codegen;
+pub mod dsl;
+pub mod migration;
+pub mod schema;
+pub mod testing;
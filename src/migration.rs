@@ -0,0 +1,197 @@
+//! Helpers to rewrite stored values after the encoding of a custom type
+//! changed
+//!
+//! Only PostgreSQL is supported, as that is the only backend enabled for
+//! this crate.
+
+use std::error::Error;
+
+use diesel::expression::helper_types::{Eq, Gt};
+use diesel::expression::{AsExpression, NonAggregate, SelectableExpression};
+use diesel::helper_types::{Filter, FindBy, Limit, Order, Select};
+use diesel::pg::{Pg, PgConnection};
+use diesel::prelude::*;
+use diesel::query_builder::update_statement::{AsChangeset, IntoUpdateTarget};
+use diesel::query_builder::{AsQuery, QueryFragment, QueryId, UpdateStatement};
+use diesel::query_source::Column;
+use diesel::types::HasSqlType;
+use diesel::update;
+
+/// A row `migrate_custom_type_column` could not convert
+#[derive(Debug)]
+pub struct MigrationFailure<K> {
+    /// Primary key of the row
+    pub key: K,
+    /// Error returned by the conversion
+    pub error: Box<dyn Error + Send + Sync>,
+}
+
+/// Result of `migrate_custom_type_column`
+#[derive(Debug)]
+pub struct MigrationStats<K> {
+    /// Number of rows written with the converted value
+    pub converted: usize,
+    /// Number of rows deleted between reading and writing their batch
+    pub skipped: usize,
+    /// All rows whose value could not be converted, they are left unchanged
+    pub failures: Vec<MigrationFailure<K>>,
+    /// Key of the last row that was read, pass it as `start_after` to
+    /// continue an interrupted migration
+    pub last_key: Option<K>,
+}
+
+impl<K> MigrationStats<K> {
+    /// Returns true if every row was converted
+    pub fn is_ok(&self) -> bool {
+        self.failures.is_empty()
+    }
+}
+
+// Types of the queries built by `migrate_custom_type_column`
+type PrimaryKey<T> = <T as Table>::PrimaryKey;
+type KeyedRows<T, S> = (PrimaryKey<T>, S);
+type KeyedRowsSqlType<T, S> = (<PrimaryKey<T> as Expression>::SqlType, <S as Expression>::SqlType);
+type BatchFilter<T, K> = Filter<T, Gt<PrimaryKey<T>, K>>;
+type BatchSelect<T, K, S> = Select<BatchFilter<T, K>, KeyedRows<T, S>, KeyedRowsSqlType<T, S>>;
+type BatchQuery<T, K, S> = Limit<Order<BatchSelect<T, K, S>, PrimaryKey<T>>>;
+type RowUpdate<T, K, D, N> = UpdateStatement<
+    <FindBy<T, PrimaryKey<T>, K> as IntoUpdateTarget>::Table,
+    <FindBy<T, PrimaryKey<T>, K> as IntoUpdateTarget>::WhereClause,
+    <Eq<D, N> as AsChangeset>::Changeset,
+>;
+
+/// Converts the values of `source` in all rows of `table` and writes them to
+/// `target`
+///
+/// Rows are read in batches of `batch_size`, ordered by the primary key,
+/// starting after the key `start_after`. Pass a value below every key, like
+/// `0` for serial keys, to start with the first row. Every batch is written
+/// in its own transaction, so an interrupted migration keeps the batches
+/// written so far and can be continued with `last_key` of the returned
+/// statistics. A failed conversion doesn't stop the migration, the row is
+/// left unchanged and reported with its key.
+///
+/// `source` and `target` may be the same column, if the SQL type doesn't
+/// change. Otherwise add the new column first and switch the custom type to
+/// it once the migration reports no failures.
+///
+/// ```no_run
+/// # #[macro_use] extern crate diesel;
+/// # extern crate diesel_custom_type;
+/// use diesel::prelude::*;
+/// use diesel::pg::PgConnection;
+/// use diesel_custom_type::migration::migrate_custom_type_column;
+///
+/// table! {
+///     tasks (id) {
+///         id -> Integer,
+///         status -> SmallInt,
+///         status_label -> Nullable<Text>,
+///     }
+/// }
+///
+/// fn label(code: i16) -> Result<Option<String>, Box<std::error::Error + Send + Sync>> {
+///     match code {
+///         1 => Ok(Some("open".into())),
+///         2 => Ok(Some("closed".into())),
+///         c => Err(format!("Unknown status {}", c).into()),
+///     }
+/// }
+///
+/// # fn main() {
+/// let conn = PgConnection::establish(&std::env::var("DATABASE_URL").unwrap()).unwrap();
+/// conn.execute("CREATE TEMPORARY TABLE tasks (id SERIAL PRIMARY KEY, status SMALLINT NOT NULL, status_label TEXT)").unwrap();
+/// conn.execute("INSERT INTO tasks (status) VALUES (1), (2), (7), (1), (2)").unwrap();
+///
+/// let stats = migrate_custom_type_column(&conn, tasks::table, tasks::status, tasks::status_label, 2, 0, label).unwrap();
+/// assert_eq!(stats.converted, 4);
+/// assert_eq!(stats.skipped, 0);
+/// assert_eq!(stats.failures.len(), 1);
+/// assert_eq!(stats.failures[0].key, 3);
+/// assert_eq!(stats.failures[0].error.to_string(), "Unknown status 7");
+/// assert_eq!(stats.last_key, Some(5));
+///
+/// let labels = tasks::table.select(tasks::status_label).order(tasks::id).load::<Option<String>>(&conn).unwrap();
+/// assert_eq!(labels[1], Some("closed".to_string()));
+/// assert_eq!(labels[2], None);
+///
+/// // resuming after the last row finds nothing left to do
+/// let stats = migrate_custom_type_column(&conn, tasks::table, tasks::status, tasks::status_label, 2, 5, label).unwrap();
+/// assert_eq!(stats.converted, 0);
+/// assert_eq!(stats.last_key, None);
+/// # }
+/// ```
+pub fn migrate_custom_type_column<T, S, D, K, Old, New, F>(
+    conn: &PgConnection,
+    table: T,
+    source: S,
+    target: D,
+    batch_size: i64,
+    start_after: K,
+    convert: F,
+) -> QueryResult<MigrationStats<K>>
+where
+    T: Table + Copy,
+    PrimaryKey<T>: Column<Table = T> + Copy,
+    S: Column<Table = T> + SelectableExpression<T> + NonAggregate + Copy,
+    D: Column<Table = T> + Copy,
+    K: AsExpression<<PrimaryKey<T> as Expression>::SqlType> + Clone,
+    New: AsExpression<D::SqlType>,
+    F: Fn(Old) -> Result<New, Box<dyn Error + Send + Sync>>,
+    T: FilterDsl<Gt<PrimaryKey<T>, K>> + FilterDsl<Eq<PrimaryKey<T>, K>>,
+    BatchFilter<T, K>: SelectDsl<KeyedRows<T, S>, KeyedRowsSqlType<T, S>>,
+    BatchSelect<T, K, S>: OrderDsl<PrimaryKey<T>>,
+    Order<BatchSelect<T, K, S>, PrimaryKey<T>>: LimitDsl,
+    BatchQuery<T, K, S>: LoadDsl<PgConnection>,
+    Pg: HasSqlType<<BatchQuery<T, K, S> as AsQuery>::SqlType>,
+    (K, Old): Queryable<<BatchQuery<T, K, S> as AsQuery>::SqlType, Pg>,
+    FindBy<T, PrimaryKey<T>, K>: IntoUpdateTarget<Table = T>,
+    Eq<D, New>: AsChangeset<Target = T>,
+    RowUpdate<T, K, D, New>: AsQuery + QueryFragment<Pg> + QueryId,
+{
+    let key = table.primary_key();
+    let mut stats = MigrationStats {
+        converted: 0,
+        skipped: 0,
+        failures: Vec::new(),
+        last_key: None,
+    };
+    let mut after = start_after;
+    loop {
+        let rows = table
+            .filter(key.gt(after.clone()))
+            .select((key, source))
+            .order(key)
+            .limit(batch_size)
+            .load::<(K, Old)>(conn)?;
+        let last = match rows.last() {
+            Some(row) => row.0.clone(),
+            None => return Ok(stats),
+        };
+        let read = rows.len();
+
+        // counted separately, so that a failed batch is not part of the
+        // statistics
+        let (converted, skipped, failures) = conn.transaction(|| -> QueryResult<_> {
+            let (mut converted, mut skipped, mut failures) = (0, 0, Vec::new());
+            for (row_key, old) in rows {
+                match convert(old) {
+                    Ok(new) => match update(table.filter(key.eq(row_key))).set(target.eq(new)).execute(conn)? {
+                        0 => skipped += 1,
+                        _ => converted += 1,
+                    },
+                    Err(error) => failures.push(MigrationFailure { key: row_key, error }),
+                }
+            }
+            Ok((converted, skipped, failures))
+        })?;
+        stats.converted += converted;
+        stats.skipped += skipped;
+        stats.failures.extend(failures);
+        stats.last_key = Some(last.clone());
+        if (read as i64) < batch_size {
+            return Ok(stats);
+        }
+        after = last;
+    }
+}
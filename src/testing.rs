@@ -0,0 +1,497 @@
+//! Helpers to check custom types against a live database
+//!
+//! Only PostgreSQL is supported: diesel 0.8 has no MySQL backend, and the
+//! adapters of this crate are only implemented for `Pg`.
+
+use std::convert::Infallible;
+use std::error::Error;
+use std::fmt;
+
+use diesel::backend::Backend;
+use diesel::expression::bound::Bound;
+use diesel::expression::dsl::sql;
+use diesel::pg::{Pg, PgConnection};
+use diesel::prelude::*;
+use diesel::query_builder::{AsQuery, BuildQueryResult, QueryBuilder, QueryFragment, QueryId};
+use diesel::result::{QueryResult, TransactionError};
+use diesel::types::{Bool, HasSqlType, NotNull, Nullable, Text, ToSql};
+use diesel::{select, ExecuteDsl, LoadDsl, Queryable};
+
+use {CustomSqlType, Variants};
+
+/// A value that did not survive the round trip through the database
+#[derive(Debug)]
+pub struct Mismatch<T> {
+    /// Position of the value in the checked slice. The `NULL` row comes after
+    /// all values, at `values.len()`.
+    pub index: usize,
+    /// Column the value was read from, `"value"` or `"nullable_value"`
+    pub column: &'static str,
+    /// The value that was inserted, `None` for the `NULL` row
+    pub expected: Option<T>,
+    /// What was read back, or the error of serializing or inserting the
+    /// value
+    pub found: QueryResult<Option<T>>,
+}
+
+/// Result of `verify_custom_type`
+#[derive(Debug)]
+pub struct RoundTripReport<T> {
+    /// Number of values read back, counting both columns and the `NULL` row
+    pub checked: usize,
+    /// All round trips that failed or returned a different value
+    pub mismatches: Vec<Mismatch<T>>,
+}
+
+impl<T> RoundTripReport<T> {
+    /// Returns true if every value was read back unchanged
+    pub fn is_ok(&self) -> bool {
+        self.mismatches.is_empty()
+    }
+}
+
+const ROUND_TRIP_TABLE: &str = "diesel_custom_type_round_trip";
+
+/// Inserts every value into a table and reads it back
+///
+/// A temporary table is created with a `NOT NULL` column `value` and a
+/// column `nullable_value`, both of the type of `T::DataBaseType`. Every
+/// value is inserted into both columns, and one more row with `NULL` in
+/// `nullable_value` checks that `None` is read back as `None`. All values
+/// are checked, mismatches are collected in the returned report instead of
+/// stopping at the first one. The outer error is returned if the table can't
+/// be created.
+///
+/// Everything happens in a transaction that is rolled back at the end, so
+/// the table is gone afterwards and the helper can also be called inside a
+/// test transaction.
+///
+/// ```no_run
+/// # extern crate diesel;
+/// # extern crate diesel_custom_type;
+/// use diesel::Connection;
+/// use diesel::pg::PgConnection;
+/// use diesel_custom_type::adapters::MacAddr;
+/// use diesel_custom_type::testing::verify_custom_type;
+///
+/// # fn main() {
+/// let conn = PgConnection::establish(&std::env::var("DATABASE_URL").unwrap()).unwrap();
+/// let report = verify_custom_type(&conn, &[MacAddr([0, 1, 2, 3, 4, 5])]).unwrap();
+/// assert!(report.is_ok(), "{:?}", report.mismatches);
+/// assert_eq!(report.checked, 3);
+/// # }
+/// ```
+pub fn verify_custom_type<T, ST>(conn: &PgConnection, values: &[T]) -> QueryResult<RoundTripReport<T>>
+where
+    T: CustomSqlType<DataBaseType = ST> + Clone + PartialEq,
+    T: ToSql<ST, Pg> + Queryable<ST, Pg>,
+    ST: QueryId + NotNull,
+    Pg: HasSqlType<ST>,
+{
+    match conn.transaction::<Infallible, _, _>(|| Err(round_trip(conn, values))) {
+        Ok(never) => match never {},
+        Err(TransactionError::UserReturnedError(report)) => report,
+        Err(TransactionError::CouldntCreateTransaction(e)) => Err(e),
+    }
+}
+
+fn round_trip<T, ST>(conn: &PgConnection, values: &[T]) -> QueryResult<RoundTripReport<T>>
+where
+    T: CustomSqlType<DataBaseType = ST> + Clone + PartialEq,
+    T: ToSql<ST, Pg> + Queryable<ST, Pg>,
+    ST: QueryId + NotNull,
+    Pg: HasSqlType<ST>,
+{
+    let oid = <Pg as HasSqlType<ST>>::metadata().oid;
+    let type_name = select(sql::<Text>(&format!("format_type({}, NULL)", oid))).get_result::<String>(conn)?;
+    conn.execute(&format!(
+        "CREATE TEMPORARY TABLE {} (id INTEGER PRIMARY KEY, value {ty} NOT NULL, nullable_value {ty})",
+        ROUND_TRIP_TABLE,
+        ty = type_name
+    ))?;
+
+    let mut report = RoundTripReport {
+        checked: 0,
+        mismatches: Vec::new(),
+    };
+    let mut check = |index, column, expected: Option<T>, found: QueryResult<Option<T>>| {
+        report.checked += 1;
+        match found {
+            Ok(ref found) if *found == expected => {}
+            found => report.mismatches.push(Mismatch {
+                index,
+                column,
+                expected,
+                found,
+            }),
+        }
+    };
+    let read_value = |index| read_column::<T, ST>(conn, "value", index).map(Some);
+    // Without the `unstable` feature diesel can't load `Option<T>` for custom
+    // types, so the `NULL` check is a separate query
+    let read_nullable_value = |index| match read_column::<bool, Bool>(conn, "nullable_value IS NULL", index) {
+        Ok(true) => Ok(None),
+        Ok(false) => read_column::<T, ST>(conn, "nullable_value", index).map(Some),
+        Err(e) => Err(e),
+    };
+
+    for (index, v) in values.iter().enumerate() {
+        match insert_row(conn, index, v, Some(v)) {
+            Ok(()) => {
+                check(index, "value", Some(v.clone()), read_value(index));
+                check(index, "nullable_value", Some(v.clone()), read_nullable_value(index));
+            }
+            Err(e) => check(index, "value", Some(v.clone()), Err(e)),
+        }
+    }
+    // `value` can't be `NULL`, so the row needs any value for it
+    if let Some(v) = values.first() {
+        let index = values.len();
+        let found = insert_row(conn, index, v, None).and_then(|()| read_nullable_value(index));
+        check(index, "nullable_value", None, found);
+    }
+
+    Ok(report)
+}
+
+// Values are serialized up front, as diesel 0.8 panics on serialization
+// errors of bind parameters. Every insert runs in its own savepoint, so a
+// value rejected by the database doesn't abort the transaction for the
+// remaining values.
+fn insert_row<T, ST>(conn: &PgConnection, index: usize, value: &T, nullable_value: Option<&T>) -> QueryResult<()>
+where
+    T: ToSql<ST, Pg> + Clone,
+    ST: QueryId + NotNull,
+    Pg: HasSqlType<ST>,
+{
+    value
+        .to_sql(&mut Vec::new())
+        .map_err(::diesel::result::Error::SerializationError)?;
+    let row = InsertRow {
+        id: index as i32,
+        value: Bound::<ST, T>::new(value.clone()),
+        nullable_value: Bound::<Nullable<ST>, Option<T>>::new(nullable_value.cloned()),
+    };
+    conn.transaction(|| row.execute(conn).map(|_| ()))
+        .map_err(|e| match e {
+            TransactionError::CouldntCreateTransaction(e) | TransactionError::UserReturnedError(e) => e,
+        })
+}
+
+fn read_column<T, ST>(conn: &PgConnection, column: &str, index: usize) -> QueryResult<T>
+where
+    T: Queryable<ST, Pg>,
+    Pg: HasSqlType<ST>,
+{
+    sql::<ST>(&format!("SELECT {} FROM {} WHERE id = {}", column, ROUND_TRIP_TABLE, index)).get_result(conn)
+}
+
+// diesel 0.8 can only build inserts into tables declared with `table!`, the
+// columns of the round trip table depend on `T`
+struct InsertRow<ST: NotNull, T> {
+    id: i32,
+    value: Bound<ST, T>,
+    nullable_value: Bound<Nullable<ST>, Option<T>>,
+}
+
+impl<ST, T> QueryFragment<Pg> for InsertRow<ST, T>
+where
+    T: ToSql<ST, Pg>,
+    ST: NotNull,
+    Pg: HasSqlType<ST>,
+{
+    fn to_sql(&self, out: &mut <Pg as Backend>::QueryBuilder) -> BuildQueryResult {
+        out.push_sql(&format!(
+            "INSERT INTO {} (id, value, nullable_value) VALUES ({}, ",
+            ROUND_TRIP_TABLE, self.id
+        ));
+        self.value.to_sql(out)?;
+        out.push_sql(", ");
+        self.nullable_value.to_sql(out)?;
+        out.push_sql(")");
+        Ok(())
+    }
+
+    fn collect_binds(&self, out: &mut <Pg as Backend>::BindCollector) -> QueryResult<()> {
+        self.value.collect_binds(out)?;
+        self.nullable_value.collect_binds(out)
+    }
+
+    fn is_safe_to_cache_prepared(&self) -> bool {
+        false
+    }
+}
+
+impl<ST: NotNull, T> QueryId for InsertRow<ST, T> {
+    type QueryId = ();
+
+    fn has_static_query_id() -> bool {
+        false
+    }
+}
+
+// The parts of the system catalogs needed to list the labels of an enum
+// type. The code generated by diesel 0.8 still uses `try!`.
+#[allow(deprecated)]
+mod catalog {
+    use diesel::types::{Nullable, Oid, Text};
+
+    // Resolves a type name like a cast would, `NULL` if there is no such type
+    sql_function!(to_regtype, to_regtype_t, (type_name: Text) -> Nullable<Oid>);
+
+    table! {
+        pg_enum (oid) {
+            oid -> Oid,
+            enumtypid -> Oid,
+            enumsortorder -> Float,
+            enumlabel -> Text,
+        }
+    }
+}
+
+/// Values that are only allowed on one side, returned by
+/// `verify_enum_labels` and `verify_allowed_values`
+///
+/// Values are compared by their `Display` output.
+///
+/// ```
+/// use diesel_custom_type::testing::DriftReport;
+///
+/// let report = DriftReport {
+///     only_in_database: vec!["furious".to_string()],
+///     only_in_code: vec![],
+/// };
+/// assert_eq!(
+///     report.to_string(),
+///     "Allowed values differ between code and database, \
+///      missing in code: [\"furious\"], missing in database: []"
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct DriftReport {
+    /// Values allowed by the database that no variant maps to. Reading a row
+    /// with one of these values fails, so the code needs a new variant.
+    pub only_in_database: Vec<String>,
+    /// Variants whose value the database does not allow. Writing one of these
+    /// fails, so the database needs a migration.
+    pub only_in_code: Vec<String>,
+}
+
+impl DriftReport {
+    fn new(database: Vec<String>, code: Vec<String>) -> Result<(), Self> {
+        let report = DriftReport {
+            only_in_database: database.iter().filter(|v| !code.contains(v)).cloned().collect(),
+            only_in_code: code.iter().filter(|v| !database.contains(v)).cloned().collect(),
+        };
+        if report.only_in_database.is_empty() && report.only_in_code.is_empty() {
+            Ok(())
+        } else {
+            Err(report)
+        }
+    }
+}
+
+impl fmt::Display for DriftReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Allowed values differ between code and database, missing in code: {:?}, missing in database: {:?}",
+            self.only_in_database, self.only_in_code
+        )
+    }
+}
+
+impl Error for DriftReport {
+    fn description(&self) -> &str {
+        "Allowed values differ between code and database"
+    }
+}
+
+/// Compares the labels of the PostgreSQL enum type `type_name` with the
+/// values listed by `Variants`
+///
+/// Meant to be called once at startup, so that a label added only to the
+/// database or only to the code is noticed before the first affected row is
+/// read or written. The outer result is the error of the catalog query, the
+/// inner one lists the labels missing on either side.
+///
+/// `type_name` is resolved like a type name in SQL: it may be schema
+/// qualified, otherwise only types in the `search_path` are found, and
+/// unquoted names are folded to lower case. A type that doesn't exist has no
+/// labels, so all variants are reported as missing in the database.
+///
+/// ```no_run
+/// # extern crate diesel;
+/// # extern crate diesel_custom_type;
+/// # use std::error::Error;
+/// use diesel::Connection;
+/// use diesel::pg::PgConnection;
+/// use diesel::types::Text;
+/// use diesel_custom_type::{CustomSqlType, Variants};
+/// use diesel_custom_type::testing::verify_enum_labels;
+///
+/// #[derive(Debug, Clone, Copy, PartialEq)]
+/// enum Mood {
+///     Happy,
+///     Sad,
+/// }
+///
+/// impl Variants for Mood {
+///     fn variants() -> &'static [Self] {
+///         &[Mood::Happy, Mood::Sad]
+///     }
+/// }
+///
+/// impl CustomSqlType for Mood {
+///     type DataBaseType = Text;
+///     type RawType = String;
+///     type Error = Box<Error + Send + Sync>;
+///
+///     fn to_database_type(&self) -> String {
+///         match *self {
+///             Mood::Happy => "happy".into(),
+///             Mood::Sad => "sad".into(),
+///         }
+///     }
+///
+///     fn from_database_type(v: &String) -> Result<Self, Self::Error> {
+///         match &**v {
+///             "happy" => Ok(Mood::Happy),
+///             "sad" => Ok(Mood::Sad),
+///             v => Err(format!("Unknown mood {}", v).into()),
+///         }
+///     }
+/// }
+///
+/// # fn main() {
+/// let conn = PgConnection::establish(&std::env::var("DATABASE_URL").unwrap()).unwrap();
+/// conn.begin_test_transaction().unwrap();
+///
+/// conn.execute("CREATE TYPE mood AS ENUM ('happy', 'sad')").unwrap();
+/// assert_eq!(verify_enum_labels::<Mood>(&conn, "mood").unwrap(), Ok(()));
+///
+/// conn.execute("ALTER TYPE mood ADD VALUE 'furious'").unwrap();
+/// let report = verify_enum_labels::<Mood>(&conn, "mood").unwrap().unwrap_err();
+/// assert_eq!(report.only_in_database, ["furious"]);
+/// assert!(report.only_in_code.is_empty());
+///
+/// conn.execute("CREATE TYPE old_mood AS ENUM ('happy')").unwrap();
+/// let report = verify_enum_labels::<Mood>(&conn, "old_mood").unwrap().unwrap_err();
+/// assert!(report.only_in_database.is_empty());
+/// assert_eq!(report.only_in_code, ["sad"]);
+///
+/// // a type of the same name in another schema is not mixed in
+/// conn.execute("CREATE SCHEMA archive").unwrap();
+/// conn.execute("CREATE TYPE archive.mood AS ENUM ('happy', 'sad', 'bored')").unwrap();
+/// let report = verify_enum_labels::<Mood>(&conn, "mood").unwrap().unwrap_err();
+/// assert_eq!(report.only_in_database, ["furious"]);
+/// let report = verify_enum_labels::<Mood>(&conn, "archive.mood").unwrap().unwrap_err();
+/// assert_eq!(report.only_in_database, ["bored"]);
+/// # }
+/// ```
+pub fn verify_enum_labels<T>(conn: &PgConnection, type_name: &str) -> QueryResult<Result<(), DriftReport>>
+where
+    T: CustomSqlType + Variants,
+    T::RawType: fmt::Display,
+{
+    use self::catalog::{pg_enum, to_regtype};
+
+    let labels = pg_enum::table
+        .select(pg_enum::enumlabel)
+        .filter(pg_enum::enumtypid.nullable().eq(to_regtype(type_name)))
+        .order(pg_enum::enumsortorder)
+        .load::<String>(conn)?;
+    Ok(DriftReport::new(labels, variant_values::<T>()))
+}
+
+/// Compares the values returned by `allowed` with the values listed by
+/// `Variants`
+///
+/// Use this for integer or text mappings, where the allowed values are
+/// defined by a lookup table or a `CHECK` constraint instead of an enum type.
+/// The query is written by the caller and has to return the raw values.
+///
+/// ```no_run
+/// # #[macro_use] extern crate diesel;
+/// # #[macro_use] extern crate diesel_custom_type;
+/// # use std::error::Error;
+/// use diesel::prelude::*;
+/// use diesel::pg::PgConnection;
+/// use diesel::types::SmallInt;
+/// use diesel_custom_type::{CustomSqlType, Variants};
+/// use diesel_custom_type::testing::verify_allowed_values;
+///
+/// #[derive(Debug, Clone, Copy, PartialEq)]
+/// enum Status {
+///     Open = 1,
+///     Closed = 2,
+/// }
+///
+/// impl Variants for Status {
+///     fn variants() -> &'static [Self] {
+///         &[Status::Open, Status::Closed]
+///     }
+/// }
+///
+/// impl CustomSqlType for Status {
+///     type DataBaseType = SmallInt;
+///     type RawType = i16;
+///     type Error = Box<Error + Send + Sync>;
+///
+///     fn to_database_type(&self) -> i16 {
+///         *self as i16
+///     }
+///
+///     fn from_database_type(v: &i16) -> Result<Self, Self::Error> {
+///         Status::variants().iter().cloned()
+///             .find(|s| *s as i16 == *v)
+///             .ok_or_else(|| format!("Unknown status {}", v).into())
+///     }
+/// }
+///
+/// table! {
+///     statuses (id) {
+///         id -> SmallInt,
+///     }
+/// }
+///
+/// # fn main() {
+/// let conn = PgConnection::establish(&std::env::var("DATABASE_URL").unwrap()).unwrap();
+/// conn.execute("CREATE TEMPORARY TABLE statuses (id SMALLINT PRIMARY KEY)").unwrap();
+///
+/// conn.execute("INSERT INTO statuses VALUES (1), (2)").unwrap();
+/// let allowed = statuses::table.select(statuses::id);
+/// assert_eq!(verify_allowed_values::<Status, _>(&conn, allowed).unwrap(), Ok(()));
+///
+/// conn.execute("INSERT INTO statuses VALUES (3)").unwrap();
+/// let report = verify_allowed_values::<Status, _>(&conn, allowed).unwrap().unwrap_err();
+/// assert_eq!(report.only_in_database, ["3"]);
+/// assert!(report.only_in_code.is_empty());
+///
+/// conn.execute("DELETE FROM statuses WHERE id <> 3").unwrap();
+/// let report = verify_allowed_values::<Status, _>(&conn, allowed).unwrap().unwrap_err();
+/// assert_eq!(report.only_in_code, ["1", "2"]);
+/// # }
+/// ```
+pub fn verify_allowed_values<T, Q>(conn: &PgConnection, allowed: Q) -> QueryResult<Result<(), DriftReport>>
+where
+    T: CustomSqlType + Variants,
+    T::RawType: Queryable<<Q as AsQuery>::SqlType, Pg> + fmt::Display,
+    Q: LoadDsl<PgConnection>,
+    Pg: HasSqlType<<Q as AsQuery>::SqlType>,
+{
+    let values = allowed
+        .load::<T::RawType>(conn)?
+        .iter()
+        .map(ToString::to_string)
+        .collect();
+    Ok(DriftReport::new(values, variant_values::<T>()))
+}
+
+fn variant_values<T>() -> Vec<String>
+where
+    T: CustomSqlType + Variants,
+    T::RawType: fmt::Display,
+{
+    T::variants().iter().map(|v| v.to_database_type().to_string()).collect()
+}
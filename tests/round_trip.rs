@@ -0,0 +1,115 @@
+//! Round trips of the adapters through a live PostgreSQL database
+//!
+//! Set `DATABASE_URL` to run them, without it every test passes without
+//! connecting.
+
+extern crate diesel;
+extern crate diesel_custom_type;
+
+use std::error::Error;
+use std::fmt;
+
+use diesel::pg::{Pg, PgConnection};
+use diesel::query_builder::QueryId;
+use diesel::types::{HasSqlType, NotNull, ToSql};
+use diesel::{Connection, Queryable};
+use diesel_custom_type::adapters::*;
+use diesel_custom_type::testing::verify_custom_type;
+use diesel_custom_type::CustomSqlType;
+
+fn connection() -> Option<PgConnection> {
+    let url = ::std::env::var("DATABASE_URL").ok()?;
+    Some(PgConnection::establish(&url).expect("Failed to connect to DATABASE_URL"))
+}
+
+/// Builds the values from raw values, so the test doesn't depend on the
+/// constructors of every adapter
+fn check<T, ST>(raw: &[T::RawType])
+where
+    T: CustomSqlType<DataBaseType = ST> + Clone + PartialEq + fmt::Debug,
+    T: ToSql<ST, Pg> + Queryable<ST, Pg>,
+    ST: QueryId + NotNull,
+    Pg: HasSqlType<ST>,
+{
+    let conn = match connection() {
+        Some(conn) => conn,
+        None => return,
+    };
+    let values = raw
+        .iter()
+        .map(|v| T::from_database_type(v).map_err(Into::<Box<dyn Error + Send + Sync>>::into))
+        .collect::<Result<Vec<T>, _>>()
+        .unwrap();
+    let report = verify_custom_type(&conn, &values).unwrap();
+    assert!(report.is_ok(), "{:?}", report.mismatches);
+    assert_eq!(report.checked, 2 * values.len() + 1);
+}
+
+fn strings(v: &[&str]) -> Vec<String> {
+    v.iter().map(|v| v.to_string()).collect()
+}
+
+#[test]
+fn text_adapters() {
+    check::<DbPath, _>(&strings(&["/srv/uploads/résumé ✓.pdf", "relative/path", ""]));
+    check::<Email, _>(&strings(&["sean@example.com", "tess+tag@sub.example.org"]));
+    check::<HexDigest32, _>(&strings(&[&"ab".repeat(32)]));
+    check::<MacAddr, _>(&strings(&["aa:bb:cc:0d:ee:ff", "00:00:00:00:00:00"]));
+    check::<PhoneE164, _>(&strings(&["+4930123456", "+15555550123"]));
+}
+
+#[test]
+fn binary_adapters() {
+    check::<Digest32, _>(&[vec![0; 32], (0..32).collect()]);
+}
+
+#[test]
+fn integer_adapters() {
+    check::<NonZeroInt, _>(&[1, -1, i32::MAX, i32::MIN]);
+    check::<NonZeroBigInt, _>(&[1, -1, i64::MAX, i64::MIN]);
+    check::<SentinelNone<NonZeroInt, -1>, _>(&[-1, 1, i32::MAX]);
+}
+
+#[cfg(feature = "not-nan")]
+#[test]
+fn float_adapters() {
+    check::<NotNan, _>(&[0.0, -0.5, 1e300, f64::INFINITY, f64::NEG_INFINITY]);
+    check::<Finite, _>(&[0.0, f64::MIN_POSITIVE, f64::MAX]);
+}
+
+#[cfg(feature = "wkt-point")]
+#[test]
+fn point_adapters() {
+    check::<WktPoint, _>(&strings(&["POINT(30.5 50.4)", "POINT(-0.1 0)"]));
+    check::<LatLng, _>(&strings(&["50.4,30.5", "-33.86,151.21"]));
+}
+
+#[cfg(feature = "base64")]
+#[test]
+fn base64_adapters() {
+    check::<Base64Bytes, _>(&strings(&["", "aGVsbG8=", "AAEC"]));
+}
+
+#[cfg(feature = "country-code")]
+#[test]
+fn country_code_adapters() {
+    check::<CountryCode, _>(&strings(&["DE", "US"]));
+}
+
+#[cfg(feature = "currency-code")]
+#[test]
+fn currency_code_adapters() {
+    check::<CurrencyCode, _>(&strings(&["EUR", "JPY"]));
+}
+
+#[cfg(feature = "language-tag")]
+#[test]
+fn language_tag_adapters() {
+    check::<LanguageTag, _>(&strings(&["en", "de-CH", "zh-Hant-TW"]));
+}
+
+#[cfg(feature = "mime")]
+#[test]
+fn mime_adapters() {
+    check::<DbMime, _>(&strings(&["text/plain", "application/json; charset=utf-8"]));
+}